@@ -0,0 +1,68 @@
+// Times building, fleeing and cache hits for the AI's distance maps on a big cave map.
+// `cargo bench`
+use rust_roguelike::prelude::*;
+use std::time::Instant;
+
+const ROUNDS: u32 = 50;
+const SEED: u64 = 1;
+
+fn main() {
+    for topology in [Topology::EightWay, Topology::FourWay] {
+        let mut rng = RandomNumberGenerator::seeded(SEED);
+        let map = MapBuilder::new(&mut rng, Some(ArchitectType::Caves), Some(Point::new(400, 300)), topology).map;
+        bench(map);
+    }
+}
+
+fn bench(mut map: Map) {
+    let floor: Vec<usize> = (0..map.tiles.len())
+        .filter(|idx| map.can_enter_tile(map.index_to_point2d(*idx)))
+        .collect();
+    println!("{}x{} {:?} map, {} floor tiles, {} rounds each", map.width, map.height, map.topology, floor.len(), ROUNDS);
+
+    let target = |round: u32| floor[spread(round, floor.len())];
+    time("chase map, one goal", &mut |round| {
+        DistanceMap::new(&map, &[target(round)]);
+    });
+    time("chase map, 32 goals", &mut |round| {
+        let goals: Vec<usize> = (0..32).map(|i| target(round * 32 + i)).collect();
+        DistanceMap::new(&map, &goals);
+    });
+    let chase = DistanceMap::new(&map, &[target(0)]);
+    time("flee map from a chase map", &mut |_| {
+        chase.flee(&map);
+    });
+    let mut maps = DistanceMaps::default();
+    time("moving target, rebuilt", &mut |round| {
+        maps.around(&map, map.index_to_point2d(target(round)));
+    });
+    maps.around(&map, map.index_to_point2d(target(0)));
+    time("same target, cached", &mut |_| {
+        maps.around(&map, map.index_to_point2d(target(0)));
+    });
+
+    // Half the floor seen, as if partway through exploring it
+    let half_width = map.width / 2;
+    for idx in 0..map.tiles.len() {
+        map.revealed_tiles[idx] = map.index_to_point2d(idx).x < half_width;
+    }
+    time("explore map, rebuilt", &mut |_| {
+        DistanceMaps::default().explore(&map);
+    });
+    maps.explore(&map);
+    time("explore map, unchanged frontier", &mut |_| {
+        maps.explore(&map);
+    });
+    println!();
+}
+
+fn time(label: &str, run: &mut dyn FnMut(u32)) {
+    let start = Instant::now();
+    (0..ROUNDS).for_each(run);
+    println!("{:<32}{:>10.3} ms", label, start.elapsed().as_secs_f64() * 1000.0 / f64::from(ROUNDS));
+}
+
+// Spreads the goals over the floor the same way every run
+fn spread(round: u32, len: usize) -> usize {
+    (round as usize).wrapping_mul(2_654_435_761) % len
+}
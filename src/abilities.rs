@@ -0,0 +1,256 @@
+use crate::prelude::*;
+use serde::Deserialize;
+use std::fs::File;
+
+const ABILITIES_FILE: &str = "resources/abilities.ron";
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum Targeting {
+    // An open tile reached in a straight, unobstructed line; only that tile is affected
+    Path,
+    // An open tile in line of sight; everything within `radius` of it is caught
+    Blast { radius: i32 },
+    // An enemy in line of sight; the bolt then arcs up to `bounces` times, each to the nearest
+    // enemy not yet struck within `jump` tiles and in sight of the last one
+    Chain { bounces: i32, jump: i32 },
+    // No aiming: centred on the caster, catching everything within `radius` (0 is the caster alone)
+    Caster { radius: i32 },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub struct Affliction {
+    pub effect: StatusEffect,
+    pub turns: i32,
+    pub magnitude: i32,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub enum AbilityEffect {
+    // Moves the caster onto the target tile
+    Teleport,
+    // Moves the caster to a random open tile within range
+    Blink,
+    // Hurts every enemy in the affected area, afflicting any that survive
+    Damage { amount: i32, inflicts: Option<Affliction> },
+    // Restores the caster's health
+    Heal { amount: i32 },
+    // Puts a helpful status effect on the caster
+    Empower { grants: Affliction },
+    // Calls up an ally on the target tile that fights for `turns` turns before fading
+    Summon { name: String, turns: i32, health: i32, power: i32 },
+}
+
+// Where everything a spell can strike or be blocked by is standing
+pub struct Creatures {
+    pub enemies: Vec<Point>,
+    pub allies: Vec<Point>,
+}
+
+impl Creatures {
+    pub fn gather<W: EntityStore>(ecs: &W) -> Self {
+        Self {
+            enemies: <&Point>::query().filter(component::<Enemy>()).iter(ecs).copied().collect(),
+            allies: <&Point>::query().filter(component::<Ally>()).iter(ecs).copied().collect(),
+        }
+    }
+
+    pub fn is_occupied(&self, pos: Point) -> bool {
+        self.enemies.contains(&pos) || self.allies.contains(&pos)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Ability {
+    pub name: String,
+    pub key: char,
+    pub cost: i32,
+    pub range: i32,
+    pub color: (u8, u8, u8),
+    pub targeting: Targeting,
+    pub effect: AbilityEffect,
+    // One line of flavour for the HUD while aiming
+    #[serde(default)]
+    pub description: String,
+    // Turns for a spent charge to come back; 0 means the ability is never held back
+    #[serde(default)]
+    pub cooldown: i32,
+    #[serde(default = "single_charge")]
+    pub max_charges: i32,
+    #[serde(skip)]
+    pub charges: i32,
+    // Turns until the next charge returns
+    #[serde(skip)]
+    pub recharge: i32,
+}
+
+fn single_charge() -> i32 {
+    1
+}
+
+impl Ability {
+    pub fn valid_target(&self, map: &Map, creatures: &Creatures, caster: Point, target: Point) -> bool {
+        if let Targeting::Caster { .. } = self.targeting {
+            return target == caster;
+        }
+        let distance = DistanceAlg::Pythagoras.distance2d(caster, target);
+        let in_reach = distance > 0.0
+            && distance <= self.range as f32
+            && map.can_enter_tile(target)
+            && map.has_line_of_sight(caster, target);
+        match (&self.targeting, &self.effect) {
+            (Targeting::Chain { .. }, _) => in_reach && creatures.enemies.contains(&target),
+            // Nothing can land on or be summoned into an occupied tile
+            (_, AbilityEffect::Teleport) | (_, AbilityEffect::Summon { .. }) => in_reach && !creatures.is_occupied(target),
+            _ => in_reach,
+        }
+    }
+
+    // Every tile the spell touches when aimed at `target`; a chain lists its hits in order
+    pub fn area(&self, map: &Map, creatures: &Creatures, caster: Point, target: Point) -> Vec<Point> {
+        match self.targeting {
+            Targeting::Path => vec![target],
+            Targeting::Blast { radius } => circle(target, radius),
+            Targeting::Caster { radius } => circle(caster, radius),
+            Targeting::Chain { bounces, jump } => {
+                let mut hits = vec![target];
+                let mut current = target;
+                for _ in 0..bounces {
+                    let next = creatures.enemies
+                        .iter()
+                        .filter(|pos| !hits.contains(pos))
+                        .filter(|pos| DistanceAlg::Pythagoras.distance2d(current, **pos) <= jump as f32)
+                        .filter(|pos| map.has_line_of_sight(current, **pos))
+                        .min_by_key(|pos| DistanceAlg::PythagorasSquared.distance2d(current, **pos) as i32)
+                        .copied();
+                    match next {
+                        Some(next) => {
+                            hits.push(next);
+                            current = next;
+                        }
+                        None => break,
+                    }
+                }
+                hits
+            }
+        }
+    }
+
+    // Open, empty tiles a blink could drop the caster onto
+    pub fn blink_destinations(&self, map: &Map, creatures: &Creatures, caster: Point) -> Vec<Point> {
+        circle(caster, self.range)
+            .into_iter()
+            .filter(|pos| *pos != caster && map.can_enter_tile(*pos) && !creatures.is_occupied(*pos))
+            .collect()
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.cooldown == 0 || self.charges > 0
+    }
+
+    pub fn spend_charge(&mut self) {
+        if self.cooldown > 0 {
+            self.charges -= 1;
+            if self.recharge == 0 {
+                self.recharge = self.cooldown;
+            }
+        }
+    }
+
+    // Called once per tick of the clock; charges come back one at a time
+    pub fn tick_cooldown(&mut self) {
+        if self.charges < self.max_charges {
+            self.recharge -= 1;
+            if self.recharge <= 0 {
+                self.charges += 1;
+                self.recharge = if self.charges < self.max_charges { self.cooldown } else { 0 };
+            }
+        }
+    }
+
+    // e.g. "Charges 1/2, next in 3" or "Cooldown 2"; empty when there's nothing to wait for
+    pub fn cooldown_status(&self) -> String {
+        if self.cooldown == 0 {
+            String::new()
+        } else if self.max_charges > 1 {
+            if self.charges < self.max_charges {
+                format!("Charges {}/{}, next in {}", self.charges, self.max_charges, self.recharge)
+            } else {
+                format!("Charges {}/{}", self.charges, self.max_charges)
+            }
+        } else if self.recharge > 0 {
+            format!("Cooldown {}", self.recharge)
+        } else {
+            String::new()
+        }
+    }
+
+    pub fn is_movement(&self) -> bool {
+        matches!(self.effect, AbilityEffect::Teleport | AbilityEffect::Blink)
+    }
+
+    pub fn is_blast(&self) -> bool {
+        matches!(self.targeting, Targeting::Blast { .. })
+    }
+
+    // Cast on the spot rather than aimed at a tile
+    pub fn is_self_cast(&self) -> bool {
+        matches!(self.targeting, Targeting::Caster { .. })
+    }
+
+    pub fn deals_damage(&self) -> bool {
+        matches!(self.effect, AbilityEffect::Damage { .. })
+    }
+
+    pub fn add_damage(&mut self, extra: i32) {
+        if let AbilityEffect::Damage { amount, .. } = &mut self.effect {
+            *amount += extra;
+        }
+    }
+
+    // e.g. "Range 6 tiles, Radius 1, Damage 3, Cost 5 mana, Cooldown 4"
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.is_self_cast() || self.effect == AbilityEffect::Blink {
+            parts.push(format!("Range {} tiles", self.range));
+        }
+        match self.targeting {
+            Targeting::Blast { radius } | Targeting::Caster { radius } if radius > 0 => parts.push(format!("Radius {}", radius)),
+            Targeting::Chain { bounces, .. } => parts.push(format!("Bounces {}", bounces)),
+            _ => {}
+        }
+        match &self.effect {
+            AbilityEffect::Damage { amount, .. } => parts.push(format!("Damage {}", amount)),
+            AbilityEffect::Heal { amount } => parts.push(format!("Heals {}", amount)),
+            AbilityEffect::Summon { turns, .. } => parts.push(format!("Lasts {} turns", turns)),
+            AbilityEffect::Empower { grants } => parts.push(format!("Lasts {} turns", grants.turns)),
+            _ => {}
+        }
+        parts.push(format!("Cost {} mana", self.cost));
+        if self.cooldown > 0 {
+            parts.push(format!("Cooldown {}", self.cooldown));
+        }
+        parts.join(", ")
+    }
+}
+
+// Every tile within `radius` of `center`; the half-tile slack lets a radius of 1 catch the diagonals too
+fn circle(center: Point, radius: i32) -> Vec<Point> {
+    let mut tiles = Vec::new();
+    for y in (center.y - radius)..=(center.y + radius) {
+        for x in (center.x - radius)..=(center.x + radius) {
+            let pos = Point::new(x, y);
+            if DistanceAlg::Pythagoras.distance2d(center, pos) <= radius as f32 + 0.5 {
+                tiles.push(pos);
+            }
+        }
+    }
+    tiles
+}
+
+pub fn load_abilities() -> Vec<Ability> {
+    let file = File::open(ABILITIES_FILE).expect("Failed opening abilities file");
+    let mut abilities: Vec<Ability> = ron::de::from_reader(file).expect("Unable to load abilities");
+    // Everything starts fully charged
+    abilities.iter_mut().for_each(|ability| ability.charges = ability.max_charges);
+    abilities
+}
@@ -0,0 +1,227 @@
+use crate::prelude::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+
+// Optional overrides, read from the working directory so players can keep their own copy
+const USER_BINDINGS_FILE: &str = "bindings.ron";
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    MoveNorth,
+    MoveSouth,
+    MoveEast,
+    MoveWest,
+    MoveNorthEast,
+    MoveNorthWest,
+    MoveSouthEast,
+    MoveSouthWest,
+    Wait,
+    PickUp,
+    Inventory,
+    Log,
+    Explore,
+    // Index into the player's Abilities
+    CastAbility(usize),
+    // Fire the spell being aimed at the targeting cursor
+    Confirm,
+    // Jump the targeting cursor to the next enemy in range; with shift held, the previous one
+    NextTarget,
+    Cancel,
+}
+
+impl Action {
+    pub const ORTHOGONAL: [Action; 4] = [Action::MoveNorth, Action::MoveWest, Action::MoveSouth, Action::MoveEast];
+    pub const DIAGONAL: [Action; 4] = [Action::MoveNorthWest, Action::MoveNorthEast, Action::MoveSouthWest, Action::MoveSouthEast];
+
+    // The step a movement action takes, which also nudges the targeting cursor
+    pub fn direction(self) -> Option<Point> {
+        match self {
+            Action::MoveNorth => Some(Point::new(0, -1)),
+            Action::MoveSouth => Some(Point::new(0, 1)),
+            Action::MoveEast => Some(Point::new(1, 0)),
+            Action::MoveWest => Some(Point::new(-1, 0)),
+            Action::MoveNorthEast => Some(Point::new(1, -1)),
+            Action::MoveNorthWest => Some(Point::new(-1, -1)),
+            Action::MoveSouthEast => Some(Point::new(1, 1)),
+            Action::MoveSouthWest => Some(Point::new(-1, 1)),
+            _ => None,
+        }
+    }
+
+    fn from_direction(direction: Point) -> Option<Action> {
+        Action::ORTHOGONAL
+            .iter()
+            .chain(Action::DIAGONAL.iter())
+            .find(|action| action.direction() == Some(direction))
+            .copied()
+    }
+}
+
+// Which key does what. A key maps to at most one action; an action may have several keys
+pub struct Bindings {
+    keys: Vec<(VirtualKeyCode, Action)>,
+}
+
+impl Bindings {
+    // The built-in keys, ability hotkeys from the abilities file, then anything the player overrides
+    pub fn load(abilities: &[Ability]) -> Self {
+        let mut bindings = Self {
+            keys: vec![
+                (VirtualKeyCode::Up, Action::MoveNorth),
+                (VirtualKeyCode::Down, Action::MoveSouth),
+                (VirtualKeyCode::Right, Action::MoveEast),
+                (VirtualKeyCode::Left, Action::MoveWest),
+                // Vi-keys
+                (VirtualKeyCode::K, Action::MoveNorth),
+                (VirtualKeyCode::J, Action::MoveSouth),
+                (VirtualKeyCode::L, Action::MoveEast),
+                (VirtualKeyCode::H, Action::MoveWest),
+                (VirtualKeyCode::U, Action::MoveNorthEast),
+                (VirtualKeyCode::Y, Action::MoveNorthWest),
+                (VirtualKeyCode::N, Action::MoveSouthEast),
+                (VirtualKeyCode::B, Action::MoveSouthWest),
+                // Numpad, with 5 standing still
+                (VirtualKeyCode::Numpad8, Action::MoveNorth),
+                (VirtualKeyCode::Numpad2, Action::MoveSouth),
+                (VirtualKeyCode::Numpad6, Action::MoveEast),
+                (VirtualKeyCode::Numpad4, Action::MoveWest),
+                (VirtualKeyCode::Numpad9, Action::MoveNorthEast),
+                (VirtualKeyCode::Numpad7, Action::MoveNorthWest),
+                (VirtualKeyCode::Numpad3, Action::MoveSouthEast),
+                (VirtualKeyCode::Numpad1, Action::MoveSouthWest),
+                (VirtualKeyCode::Space, Action::Wait),
+                (VirtualKeyCode::Numpad5, Action::Wait),
+                (VirtualKeyCode::G, Action::PickUp),
+                (VirtualKeyCode::I, Action::Inventory),
+                (VirtualKeyCode::M, Action::Log),
+                (VirtualKeyCode::X, Action::Explore),
+                (VirtualKeyCode::Return, Action::Confirm),
+                (VirtualKeyCode::NumpadEnter, Action::Confirm),
+                (VirtualKeyCode::Tab, Action::NextTarget),
+                (VirtualKeyCode::Escape, Action::Cancel),
+            ],
+        };
+        for (index, ability) in abilities.iter().enumerate() {
+            if let Some(key) = key_from_name(&ability.key.to_string()) {
+                bindings.bind(Action::CastAbility(index), &[key]);
+            }
+        }
+        if let Ok(file) = File::open(USER_BINDINGS_FILE) {
+            // Applied in Action order, so a key listed under two actions always ends up with the later one
+            match ron::de::from_reader::<_, BTreeMap<Action, Vec<String>>>(file) {
+                Ok(overrides) => overrides.iter().for_each(|(action, names)| {
+                    let keys: Vec<VirtualKeyCode> = names
+                        .iter()
+                        .filter_map(|name| {
+                            let key = key_from_name(name);
+                            if key.is_none() {
+                                eprintln!("Unknown key '{}' in {}", name, USER_BINDINGS_FILE);
+                            }
+                            key
+                        })
+                        .collect();
+                    // Only misspelt names: keep the old keys rather than leave the action unbound
+                    if keys.is_empty() && !names.is_empty() {
+                        return;
+                    }
+                    if let Some((other, _)) = overrides
+                        .iter()
+                        .filter(|(other, _)| *other > action)
+                        .find(|(_, other_names)| {
+                            other_names.iter().filter_map(|name| key_from_name(name)).any(|key| keys.contains(&key))
+                        })
+                    {
+                        eprintln!("{:?} and {:?} share a key in {}; it goes to {:?}", action, other, USER_BINDINGS_FILE, other);
+                    }
+                    bindings.bind(*action, &keys);
+                }),
+                Err(error) => eprintln!("Ignoring {}: {}", USER_BINDINGS_FILE, error),
+            }
+        }
+        bindings
+    }
+
+    // Gives `action` exactly these keys, taking them away from whatever had them before
+    fn bind(&mut self, action: Action, keys: &[VirtualKeyCode]) {
+        self.keys.retain(|(key, bound)| *bound != action && !keys.contains(key));
+        keys.iter().for_each(|key| self.keys.push((*key, action)));
+    }
+
+    pub fn action(&self, key: VirtualKeyCode) -> Option<Action> {
+        self.keys.iter().find(|(bound, _)| *bound == key).map(|(_, action)| *action)
+    }
+
+    // Like `action`, but a straight move pressed while a key for a perpendicular one is held
+    // becomes the diagonal between them, so two arrows together step diagonally
+    pub fn action_with_held(&self, key: VirtualKeyCode, held: &HeldKeys) -> Option<Action> {
+        let action = self.action(key)?;
+        let direction = match action.direction() {
+            Some(direction) if Action::ORTHOGONAL.contains(&action) => direction,
+            _ => return Some(action),
+        };
+        held.0
+            .iter()
+            .filter(|held_key| **held_key != key)
+            .filter_map(|held_key| self.action(*held_key).filter(|held| Action::ORTHOGONAL.contains(held)))
+            .filter_map(Action::direction)
+            .find(|other| other.x * direction.x + other.y * direction.y == 0)
+            .and_then(|other| Action::from_direction(direction + other))
+            .or(Some(action))
+    }
+
+    // e.g. "Up/K", or "unbound"
+    pub fn describe(&self, action: Action) -> String {
+        let names: Vec<&str> = self.keys
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .filter_map(|(key, _)| key_name(*key))
+            .collect();
+        if names.is_empty() {
+            "unbound".to_string()
+        } else {
+            names.join("/")
+        }
+    }
+
+    // e.g. "Up Left Down Right" for Action::ORTHOGONAL
+    pub fn describe_all(&self, actions: &[Action]) -> String {
+        actions
+            .iter()
+            .map(|action| self.describe(*action))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+const KEY_NAMES: [(&str, VirtualKeyCode); 61] = [
+    ("A", VirtualKeyCode::A), ("B", VirtualKeyCode::B), ("C", VirtualKeyCode::C), ("D", VirtualKeyCode::D),
+    ("E", VirtualKeyCode::E), ("F", VirtualKeyCode::F), ("G", VirtualKeyCode::G), ("H", VirtualKeyCode::H),
+    ("I", VirtualKeyCode::I), ("J", VirtualKeyCode::J), ("K", VirtualKeyCode::K), ("L", VirtualKeyCode::L),
+    ("M", VirtualKeyCode::M), ("N", VirtualKeyCode::N), ("O", VirtualKeyCode::O), ("P", VirtualKeyCode::P),
+    ("Q", VirtualKeyCode::Q), ("R", VirtualKeyCode::R), ("S", VirtualKeyCode::S), ("T", VirtualKeyCode::T),
+    ("U", VirtualKeyCode::U), ("V", VirtualKeyCode::V), ("W", VirtualKeyCode::W), ("X", VirtualKeyCode::X),
+    ("Y", VirtualKeyCode::Y), ("Z", VirtualKeyCode::Z),
+    ("0", VirtualKeyCode::Key0), ("1", VirtualKeyCode::Key1), ("2", VirtualKeyCode::Key2),
+    ("3", VirtualKeyCode::Key3), ("4", VirtualKeyCode::Key4), ("5", VirtualKeyCode::Key5),
+    ("6", VirtualKeyCode::Key6), ("7", VirtualKeyCode::Key7), ("8", VirtualKeyCode::Key8),
+    ("9", VirtualKeyCode::Key9),
+    ("Numpad0", VirtualKeyCode::Numpad0), ("Numpad1", VirtualKeyCode::Numpad1), ("Numpad2", VirtualKeyCode::Numpad2),
+    ("Numpad3", VirtualKeyCode::Numpad3), ("Numpad4", VirtualKeyCode::Numpad4), ("Numpad5", VirtualKeyCode::Numpad5),
+    ("Numpad6", VirtualKeyCode::Numpad6), ("Numpad7", VirtualKeyCode::Numpad7), ("Numpad8", VirtualKeyCode::Numpad8),
+    ("Numpad9", VirtualKeyCode::Numpad9), ("NumpadEnter", VirtualKeyCode::NumpadEnter),
+    ("Up", VirtualKeyCode::Up), ("Down", VirtualKeyCode::Down), ("Left", VirtualKeyCode::Left),
+    ("Right", VirtualKeyCode::Right), ("Home", VirtualKeyCode::Home), ("End", VirtualKeyCode::End),
+    ("PageUp", VirtualKeyCode::PageUp), ("PageDown", VirtualKeyCode::PageDown),
+    ("Space", VirtualKeyCode::Space), ("Enter", VirtualKeyCode::Return), ("Tab", VirtualKeyCode::Tab),
+    ("Escape", VirtualKeyCode::Escape), ("Comma", VirtualKeyCode::Comma), ("Period", VirtualKeyCode::Period),
+];
+
+// Names as written in the bindings file and shown in the HUD
+pub fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+    KEY_NAMES.iter().find(|(key_name, _)| key_name.eq_ignore_ascii_case(name)).map(|(_, key)| *key)
+}
+
+pub fn key_name(key: VirtualKeyCode) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(_, named)| *named == key).map(|(name, _)| *name)
+}
@@ -4,23 +4,41 @@ pub struct Camera {
     pub left_x : i32,
     pub right_x : i32,
     pub top_y : i32,
-    pub bottom_y : i32
+    pub bottom_y : i32,
+    map_width : i32,
+    map_height : i32
 }
 
 impl Camera {
-    pub fn new(player_position: Point) -> Self {
-        Self{
-            left_x : player_position.x - DISPLAY_WIDTH/2,
-            right_x : player_position.x + DISPLAY_WIDTH/2,
-            top_y : player_position.y - DISPLAY_HEIGHT/2,
-            bottom_y : player_position.y + DISPLAY_HEIGHT/2
-        }
+    pub fn new(player_position: Point, map: &Map) -> Self {
+        let mut camera = Self{
+            left_x : 0,
+            right_x : 0,
+            top_y : 0,
+            bottom_y : 0,
+            map_width : map.width,
+            map_height : map.height
+        };
+        camera.on_player_move(player_position);
+        camera
     }
 
     pub fn on_player_move(&mut self, player_position: Point) {
-        self.left_x = player_position.x - DISPLAY_WIDTH/2;
-        self.right_x = player_position.x + DISPLAY_WIDTH/2;
-        self.top_y = player_position.y - DISPLAY_HEIGHT/2;
-        self.bottom_y = player_position.y + DISPLAY_HEIGHT/2;
+        // Stop scrolling at the map edges, and centre maps smaller than the display
+        let focus_x = if self.map_width <= DISPLAY_WIDTH {
+            self.map_width / 2
+        } else {
+            player_position.x.clamp(DISPLAY_WIDTH/2, self.map_width - DISPLAY_WIDTH/2)
+        };
+        let focus_y = if self.map_height <= DISPLAY_HEIGHT {
+            self.map_height / 2
+        } else {
+            player_position.y.clamp(DISPLAY_HEIGHT/2, self.map_height - 1 - DISPLAY_HEIGHT/2)
+        };
+
+        self.left_x = focus_x - DISPLAY_WIDTH/2;
+        self.right_x = focus_x + DISPLAY_WIDTH/2;
+        self.top_y = focus_y - DISPLAY_HEIGHT/2;
+        self.bottom_y = focus_y + DISPLAY_HEIGHT/2;
     }
 }
@@ -1,4 +1,7 @@
 pub use crate::prelude::*;
+use std::collections::HashSet;
+use legion::world::EntryRef;
+use serde::Deserialize;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Render {
@@ -12,6 +15,28 @@ pub struct Player;
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Enemy;
 
+// Fights alongside `owner` until its turns run out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ally {
+    pub owner: Entity,
+    pub turns: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TravelGoal {
+    Tile(Point),
+    // Head for the nearest unexplored part of the map, again and again
+    Explore,
+}
+
+// The player walks on their own, a step per turn, until they arrive or something turns up.
+// `health` is what they had last step, so any damage taken since can be noticed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Travelling {
+    pub goal: TravelGoal,
+    pub health: i32,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct WantsToMove {
     pub entity : Entity,
@@ -30,6 +55,14 @@ pub struct Mana {
     pub max: i32
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CombatStats {
+    pub power: i32,
+    pub defense: i32,
+    pub accuracy: i32,
+    pub evasion: i32,
+}
+
 #[derive(Clone, PartialEq)]
 pub struct Name(pub String);
 
@@ -39,43 +72,300 @@ pub struct WantsToAttack {
     pub victim : Entity
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct Abilities(pub Vec<Ability>);
+
+impl Abilities {
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.0.iter().position(|ability| ability.name == name)
+    }
+
+    // A copy with `change` made to every ability `filter` picks out
+    pub fn with(&self, filter: impl Fn(&Ability) -> bool, change: impl Fn(&mut Ability)) -> Self {
+        let mut abilities = self.clone();
+        abilities.0.iter_mut().filter(|ability| filter(ability)).for_each(change);
+        abilities
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct CanDash {
-    pub cost: i32,
-    pub range: i32,
+pub struct WantsToCast {
+    pub caster: Entity,
+    // Index into the caster's Abilities
+    pub ability: usize,
+    pub target: Point,
+    pub from_scroll: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpellEffect {
+    pub tiles: Vec<Point>,
+    pub color: (u8, u8, u8),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct CanCastFireball {
-    pub cost: i32,
-    pub damage: i32,
-    pub range: i32,
+pub struct Item;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Carried(pub Entity);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ActivateItem {
+    pub used_by: Entity,
+    pub item: Entity,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct WantsToUseDash {
-    pub entity: Entity,
-    pub direction: Point,
+pub struct ProvidesHealing {
+    pub amount: i32,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct WantsToUseDashToPoint {
-    pub entity: Entity,
-    pub target: Point,
+pub struct ProvidesMana {
+    pub amount: i32,
 }
 
+#[derive(Clone, PartialEq)]
+pub struct ProvidesSpell(pub String);
+
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct WantsToUseFireball {
-    pub entity: Entity,
-    pub target: Point,
+pub enum EquipmentSlot {
+    Weapon,
+    Armor,
+    Trinket,
+}
+
+impl EquipmentSlot {
+    pub fn name(self) -> &'static str {
+        match self {
+            EquipmentSlot::Weapon => "Weapon",
+            EquipmentSlot::Armor => "Armor",
+            EquipmentSlot::Trinket => "Trinket",
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct FireballEffect {
-    pub center: Point,
-    pub radius: i32,
+pub struct Equippable {
+    pub slot: EquipmentSlot,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Equipped {
+    pub owner: Entity,
+    pub slot: EquipmentSlot,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EquipmentBonus {
+    pub power: i32,
+    pub defense: i32,
+    pub max_mana: i32,
+    pub spell_damage: i32,
+    pub dash_range: i32,
+}
+
+impl EquipmentBonus {
+    // Sum of everything `owner` is wearing
+    pub fn worn_by<W: EntityStore>(ecs: &W, owner: Entity) -> Self {
+        <(&Equipped, &EquipmentBonus)>::query()
+            .iter(ecs)
+            .filter(|(equipped, _)| equipped.owner == owner)
+            .fold(Self::default(), |total, (_, bonus)| total.plus(*bonus))
+    }
+
+    // What is worn by `owner` in `slot`, if anything
+    pub fn in_slot<W: EntityStore>(ecs: &W, owner: Entity, slot: EquipmentSlot) -> Option<(Entity, Self)> {
+        <(Entity, &Equipped, &EquipmentBonus)>::query()
+            .iter(ecs)
+            .find(|(_, equipped, _)| equipped.owner == owner && equipped.slot == slot)
+            .map(|(entity, _, bonus)| (*entity, *bonus))
+    }
+
+    // The slot `item` would go into and how `wearer` would change by swapping it in
+    pub fn compare<W: EntityStore>(ecs: &W, wearer: Entity, item: Entity) -> Option<(EquipmentSlot, Self)> {
+        let entry = ecs.entry_ref(item).ok()?;
+        let slot = entry.get_component::<Equippable>().ok()?.slot;
+        let bonus = entry.get_component::<EquipmentBonus>().copied().unwrap_or_default();
+        let worn = Self::in_slot(ecs, wearer, slot).map_or_else(Self::default, |(_, worn)| worn);
+        Some((slot, bonus.minus(worn)))
+    }
+
+    pub fn plus(self, other: Self) -> Self {
+        Self {
+            power: self.power + other.power,
+            defense: self.defense + other.defense,
+            max_mana: self.max_mana + other.max_mana,
+            spell_damage: self.spell_damage + other.spell_damage,
+            dash_range: self.dash_range + other.dash_range,
+        }
+    }
+
+    pub fn minus(self, other: Self) -> Self {
+        Self {
+            power: self.power - other.power,
+            defense: self.defense - other.defense,
+            max_mana: self.max_mana - other.max_mana,
+            spell_damage: self.spell_damage - other.spell_damage,
+            dash_range: self.dash_range - other.dash_range,
+        }
+    }
+
+    // e.g. "+2 power, -1 defense"; unchanged stats are left out
+    pub fn describe(self) -> String {
+        let stats = [
+            (self.power, "power"),
+            (self.defense, "defense"),
+            (self.max_mana, "max mana"),
+            (self.spell_damage, "spell damage"),
+            (self.dash_range, "dash range"),
+        ];
+        let parts: Vec<String> = stats
+            .iter()
+            .filter(|(value, _)| *value != 0)
+            .map(|(value, stat)| format!("{:+} {}", value, stat))
+            .collect();
+        if parts.is_empty() {
+            "no change".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ItemType {
+    HealingPotion,
+    ManaPotion,
+    FireballScroll,
+    RustyDagger,
+    ShinySword,
+    LeatherArmor,
+    ChainMail,
+    AmuletOfMana,
+    EmberRing,
+    FeatherCharm,
+}
+
+impl ItemType {
+    pub fn random(rng: &mut RandomNumberGenerator) -> Self {
+        match rng.range(0, 20) {
+            0..=6 => ItemType::HealingPotion,
+            7..=10 => ItemType::ManaPotion,
+            11..=12 => ItemType::FireballScroll,
+            13 => ItemType::RustyDagger,
+            14 => ItemType::ShinySword,
+            15 => ItemType::LeatherArmor,
+            16 => ItemType::ChainMail,
+            17 => ItemType::AmuletOfMana,
+            18 => ItemType::EmberRing,
+            _ => ItemType::FeatherCharm,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum StatusEffect {
+    Burning,
+    Poisoned,
+    Stunned,
+    Slowed,
+    Hasted,
+    Regenerating,
+}
+
+impl StatusEffect {
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusEffect::Burning => "Burning",
+            StatusEffect::Poisoned => "Poisoned",
+            StatusEffect::Stunned => "Stunned",
+            StatusEffect::Slowed => "Slowed",
+            StatusEffect::Hasted => "Hasted",
+            StatusEffect::Regenerating => "Regenerating",
+        }
+    }
+
+    pub fn color(self) -> (u8, u8, u8) {
+        match self {
+            StatusEffect::Burning => ORANGE,
+            StatusEffect::Poisoned => GREEN,
+            StatusEffect::Stunned => YELLOW,
+            StatusEffect::Slowed => CYAN,
+            StatusEffect::Hasted => GOLD,
+            StatusEffect::Regenerating => PINK,
+        }
+    }
+
+    // `magnitude` is damage or healing per turn; stun, slow and haste ignore it
+    pub fn apply(self, commands: &mut CommandBuffer, target: Entity, turns: i32, magnitude: i32) {
+        match self {
+            StatusEffect::Burning => commands.add_component(target, Burning{ damage: magnitude, turns }),
+            StatusEffect::Poisoned => commands.add_component(target, Poisoned{ damage: magnitude, turns }),
+            StatusEffect::Stunned => commands.add_component(target, Stunned{ turns }),
+            StatusEffect::Slowed => commands.add_component(target, Slowed{ turns }),
+            StatusEffect::Hasted => commands.add_component(target, Hasted{ turns }),
+            StatusEffect::Regenerating => commands.add_component(target, Regenerating{ amount: magnitude, turns }),
+        }
+    }
+
+    pub fn active_on(entry: &EntryRef) -> Vec<(StatusEffect, i32)> {
+        let mut effects = Vec::new();
+        if let Ok(burning) = entry.get_component::<Burning>() {
+            effects.push((StatusEffect::Burning, burning.turns));
+        }
+        if let Ok(poisoned) = entry.get_component::<Poisoned>() {
+            effects.push((StatusEffect::Poisoned, poisoned.turns));
+        }
+        if let Ok(stunned) = entry.get_component::<Stunned>() {
+            effects.push((StatusEffect::Stunned, stunned.turns));
+        }
+        if let Ok(slowed) = entry.get_component::<Slowed>() {
+            effects.push((StatusEffect::Slowed, slowed.turns));
+        }
+        if let Ok(hasted) = entry.get_component::<Hasted>() {
+            effects.push((StatusEffect::Hasted, hasted.turns));
+        }
+        if let Ok(regenerating) = entry.get_component::<Regenerating>() {
+            effects.push((StatusEffect::Regenerating, regenerating.turns));
+        }
+        effects
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Burning {
+    pub damage: i32,
+    pub turns: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Poisoned {
     pub damage: i32,
-    pub duration: i32,
+    pub turns: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stunned {
+    pub turns: i32,
+}
+
+// Halves speed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Slowed {
+    pub turns: i32,
+}
+
+// Doubles speed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hasted {
+    pub turns: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Regenerating {
+    pub amount: i32,
+    pub turns: i32,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -86,6 +376,37 @@ pub struct WaveManager {
     pub spawn_timer: i32,
 }
 
+impl WaveManager {
+    pub fn new() -> Self {
+        Self {
+            current_wave: 1,
+            enemies_remaining: 0,
+            wave_active: false,
+            spawn_timer: 2,
+        }
+    }
+
+    pub fn floor_cleared(&self) -> bool {
+        !self.wave_active && self.current_wave > WAVES_PER_LEVEL
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DungeonLevel(pub i32);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Seed(pub u64);
+
+// Every key held down this frame, for shift and two-arrow diagonals
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeldKeys(pub HashSet<VirtualKeyCode>);
+
+impl HeldKeys {
+    pub fn shift(&self) -> bool {
+        self.0.contains(&VirtualKeyCode::LShift) || self.0.contains(&VirtualKeyCode::RShift)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum EnemyType {
     Weak,
@@ -93,12 +414,165 @@ pub enum EnemyType {
     Boss,
 }
 
+impl EnemyType {
+    // How far it can spot the player, against the player's own 8
+    pub fn sight(self) -> i32 {
+        match self {
+            EnemyType::Weak => 6,
+            EnemyType::Medium => 7,
+            EnemyType::Boss => 5,
+        }
+    }
+
+    // Energy gained per tick, against the player's 100
+    pub fn speed(self) -> i32 {
+        match self {
+            EnemyType::Weak => 75,
+            EnemyType::Medium => 50,
+            EnemyType::Boss => 35,
+        }
+    }
+
+    // Experience awarded for the kill
+    pub fn xp(self) -> i32 {
+        match self {
+            EnemyType::Weak => 2,
+            EnemyType::Medium => 5,
+            EnemyType::Boss => 15,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct EnemyStats {
     pub enemy_type: EnemyType,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct FollowsPlayer {
-    pub move_timer: i32,
-}
\ No newline at end of file
+pub struct Experience {
+    pub level: i32,
+    pub current: i32,
+    pub next_level: i32,
+    // Level-ups not yet spent on the upgrade screen
+    pub unspent: i32,
+}
+
+impl Experience {
+    pub fn new() -> Self {
+        Self {
+            level: 1,
+            current: 0,
+            next_level: Self::needed_for(2),
+            unspent: 0,
+        }
+    }
+
+    pub fn needed_for(level: i32) -> i32 {
+        level * 10
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GainExperience {
+    pub entity: Entity,
+    pub amount: i32,
+}
+
+// Everything that takes turns banks `speed` energy per tick of the clock and acts whenever it
+// holds a full turn's worth, so a speed of 50 acts half as often as the player's 100
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Energy {
+    pub speed: i32,
+    pub current: i32,
+}
+
+impl Energy {
+    pub const TURN: i32 = 100;
+
+    pub fn new(speed: i32) -> Self {
+        Self { speed, current: 0 }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.current >= Self::TURN
+    }
+}
+
+// Marks the monsters and allies whose energy came up in this pass of the monster schedule
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MyTurn;
+
+// Message: the clock wound on this many ticks, each as long as a turn at normal speed, before
+// anyone could act in this pass. Recharging goes by these so haste doesn't speed it up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimePassed {
+    pub ticks: i32,
+}
+
+// How a monster spends its turn; picked by enemy type when it spawns
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Behaviour {
+    // Roams at random until it notices the player, then gives chase
+    Wanderer,
+    // Holds `post`, only leaving it to fight a player that comes within `radius`
+    Guard { post: Point, radius: i32 },
+    // Hangs back and attacks from up to `range` tiles away with a clear line
+    Ranged { range: i32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Alertness {
+    // Hasn't noticed the player, so goes about its own business
+    Unaware,
+    // Saw the player at `last_seen` on its latest turn
+    Alert { last_seen: Point },
+    // Lost sight of the player and is heading to where they were last seen
+    Searching { last_seen: Point },
+}
+
+impl Alertness {
+    // For tooltips; a monster that's onto the player needs no label
+    pub fn label(self) -> Option<&'static str> {
+        match self {
+            Alertness::Unaware => Some("unaware"),
+            Alertness::Alert { .. } => None,
+            Alertness::Searching { .. } => Some("searching"),
+        }
+    }
+}
+
+// What a monster knows of the player: it sees them only within `sight` tiles with nothing in the way
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Awareness {
+    pub sight: i32,
+    pub state: Alertness,
+}
+
+// At or below this much health the monster stops fighting and runs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FleesBelow(pub i32);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldOfView {
+    pub visible_tiles: HashSet<Point>,
+    pub radius: i32,
+    pub is_dirty: bool,
+}
+
+impl FieldOfView {
+    pub fn new(radius: i32) -> Self {
+        Self {
+            visible_tiles: HashSet::new(),
+            radius,
+            is_dirty: true,
+        }
+    }
+
+    pub fn clone_dirty(&self) -> Self {
+        Self {
+            visible_tiles: HashSet::new(),
+            radius: self.radius,
+            is_dirty: true,
+        }
+    }
+}
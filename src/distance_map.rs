@@ -0,0 +1,250 @@
+use crate::prelude::*;
+use std::collections::VecDeque;
+
+// Where no goal can be walked to from
+pub const UNREACHABLE: i32 = i32::MAX;
+
+// Fleeing inverts the chase map scaled by 6/5 and then lets each step cost 5, so a monster
+// running away prefers open ground over the nearest dead end
+const FLEE_SCALE: i32 = 6;
+const FLEE_STEP: i32 = 5;
+
+// Steps from every tile to the nearest of its goals, stored flat in map index order
+#[derive(Clone, Debug, Default)]
+pub struct DistanceMap {
+    goals: Vec<usize>,
+    distances: Vec<i32>,
+}
+
+impl DistanceMap {
+    // Breadth-first from all goals at once, moving the way the map's topology allows
+    pub fn new(map: &Map, goals: &[usize]) -> Self {
+        let mut distances = vec![UNREACHABLE; map.tiles.len()];
+        let mut queue = VecDeque::new();
+        for goal in goals {
+            if distances[*goal] != 0 {
+                distances[*goal] = 0;
+                queue.push_back(*goal);
+            }
+        }
+
+        while let Some(idx) = queue.pop_front() {
+            let pos = map.index_to_point2d(idx);
+            let next_distance = distances[idx] + 1;
+            for step in map.topology.steps() {
+                let next = pos + *step;
+                if map.can_enter_tile(next) {
+                    let next_idx = map.point2d_to_index(next);
+                    if distances[next_idx] == UNREACHABLE {
+                        distances[next_idx] = next_distance;
+                        queue.push_back(next_idx);
+                    }
+                }
+            }
+        }
+
+        Self {
+            goals: goals.to_vec(),
+            distances,
+        }
+    }
+
+    // The map for getting away from this one's goals; walking downhill on it leads away from them
+    pub fn flee(&self, map: &Map) -> Self {
+        let mut distances: Vec<i32> = self.distances
+            .iter()
+            .map(|distance| if *distance == UNREACHABLE { UNREACHABLE } else { -distance * FLEE_SCALE })
+            .collect();
+        let mut seeds: Vec<(i32, usize)> = distances
+            .iter()
+            .enumerate()
+            .filter(|(_, distance)| **distance != UNREACHABLE)
+            .map(|(idx, distance)| (*distance, idx))
+            .collect();
+        seeds.sort_unstable();
+        let mut seeds = seeds.into_iter().peekable();
+        let mut relaxed = VecDeque::new();
+
+        // Every tile starts out as its own goal, so this is Dijkstra rather than a plain flood. Both
+        // queues only ever grow in distance, so taking the lower front of the two keeps it in order
+        loop {
+            let (distance, idx) = match (seeds.peek(), relaxed.front()) {
+                (Some(seed), Some(next)) if next < seed => relaxed.pop_front().unwrap(),
+                (Some(_), _) => seeds.next().unwrap(),
+                (None, Some(_)) => relaxed.pop_front().unwrap(),
+                (None, None) => break,
+            };
+            if distance > distances[idx] {
+                continue;
+            }
+            let pos = map.index_to_point2d(idx);
+            for step in map.topology.steps() {
+                let next = pos + *step;
+                if map.can_enter_tile(next) {
+                    let next_idx = map.point2d_to_index(next);
+                    if distance + FLEE_STEP < distances[next_idx] {
+                        distances[next_idx] = distance + FLEE_STEP;
+                        relaxed.push_back((distance + FLEE_STEP, next_idx));
+                    }
+                }
+            }
+        }
+
+        Self {
+            goals: self.goals.clone(),
+            distances,
+        }
+    }
+
+    pub fn is_built_for(&self, map: &Map, goals: &[usize]) -> bool {
+        self.distances.len() == map.tiles.len() && self.goals == goals
+    }
+
+    pub fn distance(&self, map: &Map, pos: Point) -> Option<i32> {
+        map.try_idx(pos)
+            .map(|idx| self.distances[idx])
+            .filter(|distance| *distance != UNREACHABLE)
+    }
+
+    // The neighbour furthest downhill that `blocked` doesn't rule out, if any is lower than `pos`
+    pub fn downhill(&self, map: &Map, pos: Point, blocked: impl Fn(Point) -> bool) -> Option<Point> {
+        let mut best = None;
+        let mut best_distance = self.distance(map, pos)?;
+        for step in map.topology.steps() {
+            let next = pos + *step;
+            if !map.can_enter_tile(next) || blocked(next) {
+                continue;
+            }
+            if let Some(distance) = self.distance(map, next) {
+                if distance < best_distance {
+                    best_distance = distance;
+                    best = Some(next);
+                }
+            }
+        }
+        best
+    }
+}
+
+// The maps the monster AI and auto-explore share, each rebuilt only when its goals change.
+// A new floor brings a new Map, so a fresh set goes in alongside it
+#[derive(Default)]
+pub struct DistanceMaps {
+    chase: DistanceMap,
+    flee: DistanceMap,
+    explore: DistanceMap,
+}
+
+impl DistanceMaps {
+    // Towards and away from `target`, usually the player
+    pub fn around(&mut self, map: &Map, target: Point) -> (&DistanceMap, &DistanceMap) {
+        let goals = [map.point2d_to_index(target)];
+        if !self.chase.is_built_for(map, &goals) {
+            self.chase = DistanceMap::new(map, &goals);
+            self.flee = self.chase.flee(map);
+        }
+        (&self.chase, &self.flee)
+    }
+
+    // Towards the closest explored floor that borders the unknown
+    pub fn explore(&mut self, map: &Map) -> &DistanceMap {
+        let frontier = frontier(map);
+        if !self.explore.is_built_for(map, &frontier) {
+            self.explore = DistanceMap::new(map, &frontier);
+        }
+        &self.explore
+    }
+}
+
+fn frontier(map: &Map) -> Vec<usize> {
+    (0..map.tiles.len())
+        .filter(|idx| map.revealed_tiles[*idx])
+        .map(|idx| map.index_to_point2d(idx))
+        .filter(|pos| map.can_enter_tile(*pos))
+        .filter(|pos| map.topology.steps().iter().any(|step| {
+            map.try_idx(*pos + *step).map_or(false, |idx| !map.revealed_tiles[idx])
+        }))
+        .map(|pos| map.point2d_to_index(pos))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One open row, so every distance is just steps along it
+    fn row(width: i32) -> Map {
+        Map::new(width, 1)
+    }
+
+    fn distances(map: &Map, distance_map: &DistanceMap) -> Vec<Option<i32>> {
+        (0..map.width).map(|x| distance_map.distance(map, Point::new(x, 0))).collect()
+    }
+
+    #[test]
+    fn new_measures_to_the_nearest_of_several_goals() {
+        let map = row(7);
+        let chase = DistanceMap::new(&map, &[0, 6]);
+        assert_eq!(distances(&map, &chase), [0, 1, 2, 3, 2, 1, 0].map(Some));
+    }
+
+    #[test]
+    fn new_leaves_walled_off_tiles_unreachable() {
+        let mut map = row(5);
+        map.tiles[2] = TileType::Wall;
+        let chase = DistanceMap::new(&map, &[0]);
+        assert_eq!(distances(&map, &chase), [Some(0), Some(1), None, None, None]);
+        assert_eq!(chase.distance(&map, Point::new(-1, 0)), None);
+    }
+
+    #[test]
+    fn flee_runs_downhill_away_from_the_goal() {
+        let mut map = row(10);
+        map.tiles[9] = TileType::Wall;
+        let flee = DistanceMap::new(&map, &[0]).flee(&map);
+        let mut pos = Point::new(1, 0);
+        while let Some(step) = flee.downhill(&map, pos, |_| false) {
+            assert_eq!(step.x, pos.x + 1);
+            pos = step;
+        }
+        assert_eq!(pos, Point::new(8, 0));
+        assert_eq!(flee.distance(&map, Point::new(9, 0)), None);
+    }
+
+    #[test]
+    fn downhill_steps_towards_the_goal_around_blocked_tiles() {
+        let map = Map::new(5, 3);
+        let chase = DistanceMap::new(&map, &[map.point2d_to_index(Point::new(0, 1))]);
+        assert_eq!(chase.downhill(&map, Point::new(2, 1), |_| false), Some(Point::new(1, 1)));
+
+        // With the straight line taken it goes diagonally, and with no lower tile it stays put
+        let detour = chase.downhill(&map, Point::new(2, 1), |tile| tile == Point::new(1, 1));
+        assert_eq!(detour.map(|tile| tile.x), Some(1));
+        assert_eq!(chase.downhill(&map, Point::new(2, 1), |tile| tile.x == 1), None);
+        assert_eq!(chase.downhill(&map, Point::new(0, 1), |_| false), None);
+    }
+
+    #[test]
+    fn around_reuses_its_maps_until_the_target_moves() {
+        let map = row(8);
+        let mut maps = DistanceMaps::default();
+        maps.around(&map, Point::new(2, 0));
+        let built = maps.chase.distances.as_ptr();
+
+        maps.around(&map, Point::new(2, 0));
+        assert_eq!(maps.chase.distances.as_ptr(), built);
+
+        let (chase, flee) = maps.around(&map, Point::new(5, 0));
+        assert_eq!(chase.distance(&map, Point::new(5, 0)), Some(0));
+        assert!(flee.distance(&map, Point::new(0, 0)) < flee.distance(&map, Point::new(4, 0)));
+    }
+
+    #[test]
+    fn around_rebuilds_for_a_new_map() {
+        let mut maps = DistanceMaps::default();
+        maps.around(&row(8), Point::new(2, 0));
+        let bigger = row(12);
+        let (chase, _) = maps.around(&bigger, Point::new(2, 0));
+        assert_eq!(chase.distance(&bigger, Point::new(11, 0)), Some(9));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::prelude::*;
+
+// The oldest entries are dropped past this, so a long run doesn't grow the log forever
+const MAX_ENTRIES: usize = 1000;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LogCategory {
+    Combat,
+    Kill,
+    Spell,
+    Status,
+    Item,
+    Wave,
+    Level,
+    Travel,
+}
+
+impl LogCategory {
+    pub fn color(self) -> (u8, u8, u8) {
+        match self {
+            LogCategory::Combat => WHITE,
+            LogCategory::Kill => RED,
+            LogCategory::Spell => CYAN,
+            LogCategory::Status => ORANGE,
+            LogCategory::Item => MAGENTA,
+            LogCategory::Wave => YELLOW,
+            LogCategory::Level => GREEN,
+            LogCategory::Travel => LIGHT_GRAY,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogEntry {
+    pub category: LogCategory,
+    pub text: String,
+}
+
+pub struct GameLog {
+    entries: Vec<LogEntry>,
+    // How many entries the history screen is scrolled up from the newest
+    pub history_offset: usize,
+}
+
+impl GameLog {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            history_offset: 0,
+        }
+    }
+
+    pub fn add<S: ToString>(&mut self, category: LogCategory, text: S) {
+        self.entries.push(LogEntry {
+            category,
+            text: text.to_string(),
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        // Counted back from the newest, so it only has to stay within what's left
+        self.history_offset = self.history_offset.min(self.entries.len());
+    }
+
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+}
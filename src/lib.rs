@@ -0,0 +1,46 @@
+#![warn(clippy::pedantic)]
+
+// The game itself, as a library so benches can build maps with it; main.rs reads the
+// command line and runs the loop
+
+mod components;
+mod spawner;
+mod map;
+mod map_builder;
+mod systems;
+mod camera;
+mod turn_state;
+mod targeting_state;
+mod game_log;
+mod upgrades;
+mod abilities;
+mod bindings;
+mod distance_map;
+
+pub mod prelude {
+    pub use bracket_lib::prelude::*;
+    pub use legion::*;
+    pub use legion::world::SubWorld;
+    pub use legion::systems::CommandBuffer;
+    pub const SCREEN_WIDTH: i32 = 80;
+    pub const SCREEN_HEIGHT: i32 = 50;
+    pub const DISPLAY_WIDTH: i32 = SCREEN_WIDTH / 2;
+    pub const DISPLAY_HEIGHT: i32 = SCREEN_HEIGHT / 2;
+    pub const WAVES_PER_LEVEL: i32 = 3;
+    pub const FINAL_LEVEL: i32 = 5;
+    pub const MAX_CARRIED: usize = 9;
+    pub use crate::components::*;
+    pub use crate::spawner::*;
+    pub use crate::map::*;
+    pub use crate::systems::*;
+    pub use crate::map_builder::*;
+    pub use crate::camera::*;
+    pub use crate::turn_state::*;
+    pub use crate::targeting_state::*;
+    pub use crate::game_log::*;
+    pub use crate::upgrades::*;
+    pub use crate::abilities::*;
+    pub use crate::bindings::*;
+    pub use crate::distance_map::*;
+    pub use smallvec::SmallVec;
+}
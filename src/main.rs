@@ -1,73 +1,190 @@
 #![warn(clippy::pedantic)]
 
-mod components;
-mod spawner;
-mod map;
-mod map_builder;
-mod systems;
-mod camera;
-mod turn_state;
-mod targeting_state;
-
-mod prelude {
-    pub use bracket_lib::prelude::*;
-    pub use legion::*;
-    pub use legion::world::SubWorld;
-    pub use legion::systems::CommandBuffer;
-    pub const SCREEN_WIDTH: i32 = 80;
-    pub const SCREEN_HEIGHT: i32 = 50;
-    pub const DISPLAY_WIDTH: i32 = SCREEN_WIDTH / 2;
-    pub const DISPLAY_HEIGHT: i32 = SCREEN_HEIGHT / 2;
-    pub use crate::components::*;
-    pub use crate::spawner::*;
-    pub use crate::map::*;
-    pub use crate::systems::*;
-    pub use crate::map_builder::*;
-    pub use crate::camera::*;
-    pub use crate::turn_state::*;
-    pub use crate::targeting_state::*;
-    pub use smallvec::SmallVec;
-}
-
-use prelude::*;
+use rust_roguelike::prelude::*;
+use std::collections::HashSet;
 
 struct State {
     ecs : World,
     resources: Resources,
+    options: RunOptions,
     input_systems: Schedule,
     player_systems: Schedule,
-    monster_systems: Schedule
+    monster_systems: Schedule,
+    game_over_systems: Schedule,
+    victory_systems: Schedule,
+    log_history_systems: Schedule,
+    inventory_systems: Schedule,
+    level_up_systems: Schedule
+}
+
+const MIN_MAP_SIZE: i32 = 20;
+
+#[derive(Clone, Copy)]
+struct RunOptions {
+    seed: u64,
+    architect: Option<ArchitectType>,
+    map_size: Option<Point>,
+    topology: Topology
+}
+
+impl RunOptions {
+    // `--seed N` replays a run, `--map rooms|caves|drunkard|bsp` forces a layout,
+    // `--size WxH` overrides that layout's default map size and `--moves 4|8` picks
+    // whether diagonals count for moving and attacking
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let value_of = |flag: &str| args.iter()
+            .position(|arg| arg == flag)
+            .and_then(|i| args.get(i + 1));
+
+        Self {
+            seed: value_of("--seed")
+                .and_then(|seed| seed.parse().ok())
+                .unwrap_or_else(|| RandomNumberGenerator::new().next_u64()),
+            architect: value_of("--map")
+                .and_then(|name| ArchitectType::from_name(name)),
+            map_size: value_of("--size")
+                .and_then(|size| size.split_once('x'))
+                .and_then(|(w, h)| Some(Point::new(w.parse::<i32>().ok()?, h.parse::<i32>().ok()?)))
+                .map(|size| Point::new(size.x.max(MIN_MAP_SIZE), size.y.max(MIN_MAP_SIZE))),
+            topology: value_of("--moves")
+                .and_then(|moves| Topology::from_name(moves))
+                .unwrap_or(Topology::EightWay)
+        }
+    }
 }
 
 impl State {
-    fn new() -> Self {
+    fn new(options: &RunOptions) -> Self {
         let mut ecs = World::default();
         let mut resources = Resources::default();
-        let mut rng = RandomNumberGenerator::new();
-        let map_builder = MapBuilder::new(&mut rng);
+        let mut rng = RandomNumberGenerator::seeded(options.seed);
+        let map_builder = MapBuilder::new(&mut rng, options.architect, options.map_size, options.topology);
         
         spawn_player(&mut ecs, map_builder.player_start);
+        spawn_level_items(
+            &mut ecs,
+            &mut rng,
+            &map_builder.map,
+            &map_builder.rooms,
+            map_builder.player_start
+        );
         
-        let wave_manager = WaveManager {
-            current_wave: 1,
-            enemies_remaining: 0,
-            wave_active: false,
-            spawn_timer: 2,
-        };
-        
+        resources.insert(Camera::new(map_builder.player_start, &map_builder.map));
         resources.insert(map_builder.map);
-        resources.insert(Camera::new(map_builder.player_start));
+        resources.insert(DistanceMaps::default());
         resources.insert(TurnState::AwaitingInput);
         resources.insert(TargetingState::None);
-        resources.insert(wave_manager);
+        resources.insert(WaveManager::new());
+        resources.insert(DungeonLevel(1));
+        resources.insert(rng);
+        resources.insert(Seed(options.seed));
+        resources.insert(GameLog::new());
+        resources.insert(UpgradeChoices(Vec::new()));
+        resources.insert(Bindings::load(&load_abilities()));
         
         Self {
             ecs,
             resources,
+            options: *options,
             input_systems: build_input_scheduler(),
             player_systems: build_player_scheduler(),
-            monster_systems: build_monster_scheduler()
+            monster_systems: build_monster_scheduler(),
+            game_over_systems: build_game_over_scheduler(),
+            victory_systems: build_victory_scheduler(),
+            log_history_systems: build_log_history_scheduler(),
+            inventory_systems: build_inventory_scheduler(),
+            level_up_systems: build_level_up_scheduler()
+        }
+    }
+
+    fn restart_on_key(&mut self, key: Option<VirtualKeyCode>) {
+        match key {
+            Some(VirtualKeyCode::Key1) => {
+                let options = RunOptions {
+                    seed: RandomNumberGenerator::new().next_u64(),
+                    ..self.options
+                };
+                *self = State::new(&options);
+            }
+            Some(VirtualKeyCode::Key2) => *self = State::new(&self.options),
+            _ => {}
+        }
+    }
+
+    fn advance_level(&mut self) {
+        let player_entity = *<Entity>::query()
+            .filter(component::<Player>())
+            .iter(&self.ecs)
+            .nth(0)
+            .unwrap();
+
+        // Everything but the player, their pack and their gear belongs to the old floor
+        let mut entities_to_keep = HashSet::new();
+        entities_to_keep.insert(player_entity);
+        <(Entity, &Carried)>::query()
+            .iter(&self.ecs)
+            .filter(|(_, carry)| carry.0 == player_entity)
+            .for_each(|(e, _)| {
+                entities_to_keep.insert(*e);
+            });
+        <(Entity, &Equipped)>::query()
+            .iter(&self.ecs)
+            .filter(|(_, equipped)| equipped.owner == player_entity)
+            .for_each(|(e, _)| {
+                entities_to_keep.insert(*e);
+            });
+
+        let mut cb = CommandBuffer::new(&self.ecs);
+        for e in Entity::query().iter(&self.ecs) {
+            if !entities_to_keep.contains(e) {
+                cb.remove(*e);
+            }
+        }
+        // Travel plans made on the old floor mean nothing on the new one
+        cb.remove_component::<Travelling>(player_entity);
+        cb.flush(&mut self.ecs);
+
+        let mut map_builder = {
+            let mut rng = self.resources.get_mut::<RandomNumberGenerator>().unwrap();
+            let map_builder = MapBuilder::new(&mut rng, self.options.architect, self.options.map_size, self.options.topology);
+            spawn_level_items(
+                &mut self.ecs,
+                &mut rng,
+                &map_builder.map,
+                &map_builder.rooms,
+                map_builder.player_start
+            );
+            map_builder
+        };
+
+        let level = {
+            let mut level = self.resources.get_mut::<DungeonLevel>().unwrap();
+            level.0 += 1;
+            level.0
+        };
+        // There is nowhere further down from the final floor
+        if level >= FINAL_LEVEL {
+            let stairs_idx = map_builder.map.point2d_to_index(map_builder.stairs);
+            map_builder.map.tiles[stairs_idx] = TileType::Floor;
         }
+
+        <(&mut Point, &mut FieldOfView)>::query()
+            .filter(component::<Player>())
+            .iter_mut(&mut self.ecs)
+            .for_each(|(pos, fov)| {
+                *pos = map_builder.player_start;
+                fov.is_dirty = true;
+            });
+
+        self.resources.get_mut::<GameLog>().unwrap()
+            .add(LogCategory::Level, format!("You descend to floor {}", level));
+        self.resources.insert(Camera::new(map_builder.player_start, &map_builder.map));
+        self.resources.insert(map_builder.map);
+        self.resources.insert(DistanceMaps::default());
+        self.resources.insert(WaveManager::new());
+        self.resources.insert(TargetingState::None);
+        self.resources.insert(TurnState::AwaitingInput);
     }
 }
 
@@ -92,9 +209,19 @@ impl GameState for State {
             (mouse_y as i32).min(DISPLAY_HEIGHT - 1).max(0)
         );
         
+        // Moving the mouse while aiming drags the targeting cursor along with it
+        let mouse_moved = self.resources.get::<Point>().map_or(false, |last| *last != mouse_tile_pos);
+        if mouse_moved {
+            let offset = {
+                let camera = self.resources.get::<Camera>().unwrap();
+                Point::new(camera.left_x, camera.top_y)
+            };
+            self.resources.get_mut::<TargetingState>().unwrap().move_cursor_to(mouse_tile_pos + offset);
+        }
         self.resources.insert(mouse_tile_pos);
         
         let input = INPUT.lock();
+        self.resources.insert(HeldKeys(input.key_pressed_set().clone()));
         let left_click = input.is_mouse_button_pressed(0);
         let right_click = input.is_mouse_button_pressed(1);
         
@@ -113,14 +240,17 @@ impl GameState for State {
         
         let should_spawn_wave = {
             let wave_manager = self.resources.get::<WaveManager>().unwrap();
-            !wave_manager.wave_active && wave_manager.spawn_timer <= 0 && wave_manager.current_wave <= 3
+            !wave_manager.wave_active && wave_manager.spawn_timer <= 0 && wave_manager.current_wave <= WAVES_PER_LEVEL
         };
         
         if should_spawn_wave {
             let wave_number = self.resources.get::<WaveManager>().unwrap().current_wave;
             let enemies_spawned = {
                 let map = self.resources.get::<Map>().unwrap();
-                spawn_wave_monsters(&mut self.ecs, &*map, wave_number)
+                let level = self.resources.get::<DungeonLevel>().unwrap().0;
+                let mut rng = self.resources.get_mut::<RandomNumberGenerator>().unwrap();
+                let mut game_log = self.resources.get_mut::<GameLog>().unwrap();
+                spawn_wave_monsters(&mut self.ecs, &mut rng, &*map, wave_number, level, &mut game_log)
             };
             
             let mut wave_manager = self.resources.get_mut::<WaveManager>().unwrap();
@@ -141,12 +271,31 @@ impl GameState for State {
             TurnState::MonsterTurn => {
                 self.monster_systems.execute(&mut self.ecs, &mut self.resources)
             }
+            TurnState::NextLevel => self.advance_level(),
+            TurnState::GameOver => {
+                self.game_over_systems.execute(&mut self.ecs, &mut self.resources);
+                self.restart_on_key(ctx.key);
+            }
+            TurnState::Victory => {
+                self.victory_systems.execute(&mut self.ecs, &mut self.resources);
+                self.restart_on_key(ctx.key);
+            }
+            TurnState::ViewingLog => {
+                self.log_history_systems.execute(&mut self.ecs, &mut self.resources);
+            }
+            TurnState::ShowingInventory => {
+                self.inventory_systems.execute(&mut self.ecs, &mut self.resources);
+            }
+            TurnState::ChoosingUpgrade => {
+                self.level_up_systems.execute(&mut self.ecs, &mut self.resources);
+            }
         }
         render_draw_buffer(ctx).expect("Render error");
     }
 }
 
 fn main() -> BError {
+    let options = RunOptions::from_args();
     let context = BTermBuilder::new()
         .with_title("Dungeon Crawler")
         .with_fps_cap(30.0)
@@ -167,5 +316,5 @@ fn main() -> BError {
             "dungeonfont.png")
         .build()?;
 
-    main_loop(context, State::new())
+    main_loop(context, State::new(&options))
 }
\ No newline at end of file
@@ -1,56 +1,130 @@
 use crate::prelude::*;
 
-const NUM_TILES: usize = (SCREEN_WIDTH * SCREEN_HEIGHT) as usize;
-
 #[derive(Copy, Clone, PartialEq)]
 pub enum TileType {
     Wall,
     Floor,
+    DownStairs,
+}
+
+const ORTHOGONAL_STEPS: [Point; 4] = [
+    Point::constant(0, -1), Point::constant(1, 0), Point::constant(0, 1), Point::constant(-1, 0),
+];
+const ALL_STEPS: [Point; 8] = [
+    Point::constant(0, -1), Point::constant(1, 0), Point::constant(0, 1), Point::constant(-1, 0),
+    Point::constant(-1, -1), Point::constant(1, -1), Point::constant(-1, 1), Point::constant(1, 1),
+];
+
+// Which neighbours count as adjacent, for moving and attacking alike
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Topology {
+    FourWay,
+    EightWay,
 }
 
-pub fn map_idx(x: i32, y: i32) -> usize {
-    ((y * SCREEN_WIDTH) + x) as usize
+impl Topology {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "4" => Some(Topology::FourWay),
+            "8" => Some(Topology::EightWay),
+            _ => None,
+        }
+    }
+
+    pub fn steps(self) -> &'static [Point] {
+        match self {
+            Topology::FourWay => &ORTHOGONAL_STEPS,
+            Topology::EightWay => &ALL_STEPS,
+        }
+    }
+
+    pub fn allows(self, step: Point) -> bool {
+        self.steps().contains(&step)
+    }
+
+    // Close enough to trade blows
+    pub fn is_adjacent(self, a: Point, b: Point) -> bool {
+        self.allows(b - a)
+    }
 }
 
 pub struct Map {
+    pub width: i32,
+    pub height: i32,
     pub tiles: Vec<TileType>,
+    pub revealed_tiles: Vec<bool>,
+    pub topology: Topology,
 }
 
 impl Map {
-    pub fn new() -> Self {
+    pub fn new(width: i32, height: i32) -> Self {
         Self {
-            tiles: vec![TileType::Floor; NUM_TILES],
+            width,
+            height,
+            tiles: vec![TileType::Floor; (width * height) as usize],
+            revealed_tiles: vec![false; (width * height) as usize],
+            topology: Topology::EightWay,
         }
     }
 
+    pub fn map_idx(&self, x: i32, y: i32) -> usize {
+        ((y * self.width) + x) as usize
+    }
+
     pub fn in_bounds(&self, point : Point) -> bool {
-        point.x >= 0 && point.x < SCREEN_WIDTH && point.y >= 0 && point.y < SCREEN_HEIGHT
+        point.x >= 0 && point.x < self.width && point.y >= 0 && point.y < self.height
     }
 
     pub fn try_idx(&self, point : Point) -> Option<usize> {
         if !self.in_bounds(point) {
             None
         } else {
-            Some(map_idx(point.x, point.y))
+            Some(self.map_idx(point.x, point.y))
         }
     }
 
     pub fn can_enter_tile(&self, point : Point) -> bool {
-        self.in_bounds(point) && self.tiles[map_idx(point.x, point.y)]==TileType::Floor
+        self.in_bounds(point) && self.tiles[self.map_idx(point.x, point.y)] != TileType::Wall
+    }
+
+    // Walks a Bresenham line, so nothing but walls in between blocks it
+    pub fn has_line_of_sight(&self, start: Point, end: Point) -> bool {
+        let mut current = start;
+        let dx = (end.x - start.x).abs();
+        let dy = (end.y - start.y).abs();
+        let sx = if start.x < end.x { 1 } else { -1 };
+        let sy = if start.y < end.y { 1 } else { -1 };
+        let mut err = dx - dy;
+
+        while current != end {
+            if current != start && !self.can_enter_tile(current) {
+                return false;
+            }
+            let e2 = 2 * err;
+            if e2 > -dy {
+                err -= dy;
+                current.x += sx;
+            }
+            if e2 < dx {
+                err += dx;
+                current.y += sy;
+            }
+        }
+        true
     }
 
     fn is_exit_valid(&self, x: i32, y: i32) -> bool {
-        if x < 1 || x > SCREEN_WIDTH-1 || y < 1 || y > SCREEN_HEIGHT-1 { 
+        if x < 1 || x > self.width-1 || y < 1 || y > self.height-1 { 
             return false; 
         }
-        let idx = map_idx(x, y);
-        self.tiles[idx] == TileType::Floor
+        let idx = self.map_idx(x, y);
+        self.tiles[idx] != TileType::Wall
     }
 }
 
 impl Algorithm2D for Map {
     fn dimensions(&self) -> Point {
-        Point::new(SCREEN_WIDTH, SCREEN_HEIGHT)
+        Point::new(self.width, self.height)
     }
 }
 
@@ -61,15 +135,19 @@ impl BaseMap for Map {
     
     fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
         let mut exits = SmallVec::new();
-        let x = idx as i32 % SCREEN_WIDTH;
-        let y = idx as i32 / SCREEN_WIDTH;
-        let w = SCREEN_WIDTH;
+        let x = idx as i32 % self.width;
+        let y = idx as i32 / self.width;
+        let w = self.width;
 
         if self.is_exit_valid(x-1, y) { exits.push((idx-1, 1.0)) };
         if self.is_exit_valid(x+1, y) { exits.push((idx+1, 1.0)) };
         if self.is_exit_valid(x, y-1) { exits.push((idx-w as usize, 1.0)) };
         if self.is_exit_valid(x, y+1) { exits.push((idx+w as usize, 1.0)) };
 
+        if self.topology == Topology::FourWay {
+            return exits;
+        }
+
         // Diagonals
         if self.is_exit_valid(x-1, y-1) { exits.push(((idx-w as usize)-1, 1.45)); }
         if self.is_exit_valid(x+1, y-1) { exits.push(((idx-w as usize)+1, 1.45)); }
@@ -80,9 +158,9 @@ impl BaseMap for Map {
     }
 
     fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
-        let w = SCREEN_WIDTH;
+        let w = self.width;
         let p1 = Point::new(idx1 as i32 % w, idx1 as i32 / w);
         let p2 = Point::new(idx2 as i32 % w, idx2 as i32 / w);
         DistanceAlg::Pythagoras.distance2d(p1, p2)
     }
-}
\ No newline at end of file
+}
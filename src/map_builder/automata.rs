@@ -0,0 +1,68 @@
+use crate::prelude::*;
+use super::MapArchitect;
+
+const ITERATIONS: usize = 10;
+
+pub struct CellularAutomataArchitect {}
+
+impl MapArchitect for CellularAutomataArchitect {
+    fn build(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::empty(width, height);
+        self.random_noise_map(rng, &mut mb.map);
+        for _ in 0..ITERATIONS {
+            self.iteration(&mut mb.map);
+        }
+        mb.player_start = mb.find_start_near_center();
+        mb
+    }
+}
+
+impl CellularAutomataArchitect {
+    fn random_noise_map(&mut self, rng: &mut RandomNumberGenerator, map: &mut Map) {
+        map.tiles.iter_mut().for_each(|t| {
+            let roll = rng.range(0, 100);
+            if roll > 55 {
+                *t = TileType::Floor;
+            } else {
+                *t = TileType::Wall;
+            }
+        });
+        // The iterations never touch the edge, so walls there have to be set up front
+        for y in 0 .. map.height {
+            for x in 0 .. map.width {
+                if x == 0 || y == 0 || x == map.width - 1 || y == map.height - 1 {
+                    let idx = map.map_idx(x, y);
+                    map.tiles[idx] = TileType::Wall;
+                }
+            }
+        }
+    }
+
+    fn count_neighbors(&self, x: i32, y: i32, map: &Map) -> usize {
+        let mut neighbors = 0;
+        for iy in -1 ..= 1 {
+            for ix in -1 ..= 1 {
+                if !(ix == 0 && iy == 0) && map.tiles[map.map_idx(x + ix, y + iy)] == TileType::Wall {
+                    neighbors += 1;
+                }
+            }
+        }
+        neighbors
+    }
+
+    fn iteration(&mut self, map: &mut Map) {
+        let mut new_tiles = map.tiles.clone();
+        for y in 1 .. map.height - 1 {
+            for x in 1 .. map.width - 1 {
+                let neighbors = self.count_neighbors(x, y, map);
+                let idx = map.map_idx(x, y);
+                if neighbors > 4 || neighbors == 0 {
+                    new_tiles[idx] = TileType::Wall;
+                } else {
+                    new_tiles[idx] = TileType::Floor;
+                }
+            }
+        }
+        map.tiles = new_tiles;
+    }
+}
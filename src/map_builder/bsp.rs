@@ -0,0 +1,85 @@
+use crate::prelude::*;
+use super::MapArchitect;
+
+const MIN_LEAF_SIZE: i32 = 10;
+
+pub struct BspArchitect {}
+
+impl MapArchitect for BspArchitect {
+    fn build(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::empty(width, height);
+        mb.fill(TileType::Wall);
+
+        let mut leaves = vec![Rect::with_exact(1, 1, width - 1, height - 1)];
+        // Keep splitting until every leaf is too small to halve again
+        loop {
+            let split_leaves: Vec<Rect> = leaves
+                .iter()
+                .flat_map(|leaf| self.split(leaf, rng))
+                .collect();
+            if split_leaves.len() == leaves.len() {
+                break;
+            }
+            leaves = split_leaves;
+        }
+
+        // Leaves come out in tree order, so neighbouring rooms are siblings
+        for leaf in leaves.iter() {
+            let room = self.room_in_leaf(leaf, rng);
+            mb.carve_room(&room);
+            mb.rooms.push(room);
+        }
+        let rooms = mb.rooms.clone();
+        for pair in rooms.windows(2) {
+            mb.connect_points(rng, pair[0].center(), pair[1].center());
+        }
+
+        mb.player_start = mb.rooms[0].center();
+        mb
+    }
+}
+
+impl BspArchitect {
+    fn split(&self, leaf: &Rect, rng: &mut RandomNumberGenerator) -> Vec<Rect> {
+        let can_split_x = leaf.width() >= MIN_LEAF_SIZE * 2;
+        let can_split_y = leaf.height() >= MIN_LEAF_SIZE * 2;
+
+        let split_x = match (can_split_x, can_split_y) {
+            (false, false) => return vec![*leaf],
+            (true, false) => true,
+            (false, true) => false,
+            (true, true) => {
+                if leaf.width() > leaf.height() {
+                    true
+                } else if leaf.height() > leaf.width() {
+                    false
+                } else {
+                    rng.range(0, 2) == 0
+                }
+            }
+        };
+
+        if split_x {
+            let cut = rng.range(leaf.x1 + MIN_LEAF_SIZE, leaf.x2 - MIN_LEAF_SIZE + 1);
+            vec![
+                Rect::with_exact(leaf.x1, leaf.y1, cut, leaf.y2),
+                Rect::with_exact(cut, leaf.y1, leaf.x2, leaf.y2),
+            ]
+        } else {
+            let cut = rng.range(leaf.y1 + MIN_LEAF_SIZE, leaf.y2 - MIN_LEAF_SIZE + 1);
+            vec![
+                Rect::with_exact(leaf.x1, leaf.y1, leaf.x2, cut),
+                Rect::with_exact(leaf.x1, cut, leaf.x2, leaf.y2),
+            ]
+        }
+    }
+
+    fn room_in_leaf(&self, leaf: &Rect, rng: &mut RandomNumberGenerator) -> Rect {
+        // Leave a one tile border inside the leaf so rooms never touch
+        let width = rng.range(leaf.width() / 2, leaf.width() - 1);
+        let height = rng.range(leaf.height() / 2, leaf.height() - 1);
+        let x = rng.range(leaf.x1 + 1, leaf.x2 - width);
+        let y = rng.range(leaf.y1 + 1, leaf.y2 - height);
+        Rect::with_size(x, y, width, height)
+    }
+}
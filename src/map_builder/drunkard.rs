@@ -0,0 +1,58 @@
+use crate::prelude::*;
+use super::MapArchitect;
+
+const STAGGER_DISTANCE: usize = 400;
+// Keep digging until this fraction of the map is floor
+const DESIRED_FLOOR_DIVISOR: usize = 3;
+
+pub struct DrunkardsWalkArchitect {}
+
+impl MapArchitect for DrunkardsWalkArchitect {
+    fn build(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::empty(width, height);
+        mb.fill(TileType::Wall);
+        let desired_floor = mb.map.tiles.len() / DESIRED_FLOOR_DIVISOR;
+        let center = Point::new(width / 2, height / 2);
+        mb.player_start = center;
+        self.drunkard(center, rng, &mut mb.map);
+        while mb.map.tiles.iter().filter(|t| **t == TileType::Floor).count() < desired_floor {
+            self.drunkard(
+                Point::new(rng.range(1, width - 1), rng.range(1, height - 1)),
+                rng,
+                &mut mb.map
+            );
+            // Drunkards that wandered off on their own get walled back up
+            mb.prune_unreachable();
+        }
+        mb
+    }
+}
+
+impl DrunkardsWalkArchitect {
+    fn drunkard(&mut self, start: Point, rng: &mut RandomNumberGenerator, map: &mut Map) {
+        let mut drunkard_pos = start;
+        let mut distance_staggered = 0;
+
+        loop {
+            let drunk_idx = map.point2d_to_index(drunkard_pos);
+            map.tiles[drunk_idx] = TileType::Floor;
+
+            match rng.range(0, 4) {
+                0 => drunkard_pos.x -= 1,
+                1 => drunkard_pos.x += 1,
+                2 => drunkard_pos.y -= 1,
+                _ => drunkard_pos.y += 1,
+            }
+            // Keep a solid wall around the edge of the map
+            if drunkard_pos.x < 1 || drunkard_pos.x > map.width - 2 ||
+               drunkard_pos.y < 1 || drunkard_pos.y > map.height - 2 {
+                break;
+            }
+
+            distance_staggered += 1;
+            if distance_staggered > STAGGER_DISTANCE {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use crate::prelude::*;
+
+mod automata;
+mod bsp;
+mod drunkard;
+mod rooms;
+
+use automata::CellularAutomataArchitect;
+use bsp::BspArchitect;
+use drunkard::DrunkardsWalkArchitect;
+use rooms::RoomsArchitect;
+
+trait MapArchitect {
+    fn build(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ArchitectType {
+    Rooms,
+    Caves,
+    Drunkard,
+    Bsp,
+}
+
+impl ArchitectType {
+    pub fn random(rng: &mut RandomNumberGenerator) -> Self {
+        match rng.range(0, 4) {
+            0 => ArchitectType::Rooms,
+            1 => ArchitectType::Caves,
+            2 => ArchitectType::Drunkard,
+            _ => ArchitectType::Bsp,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "rooms" => Some(ArchitectType::Rooms),
+            "caves" => Some(ArchitectType::Caves),
+            "drunkard" => Some(ArchitectType::Drunkard),
+            "bsp" => Some(ArchitectType::Bsp),
+            _ => None,
+        }
+    }
+
+    // Caves read best with room to sprawl; the others are sized to the screen
+    pub fn default_dimensions(self) -> Point {
+        match self {
+            ArchitectType::Caves => Point::new(120, 80),
+            _ => Point::new(SCREEN_WIDTH, SCREEN_HEIGHT),
+        }
+    }
+}
+
+pub struct MapBuilder {
+    pub map : Map,
+    pub rooms : Vec<Rect>,
+    pub player_start : Point,
+    pub stairs : Point
+}
+
+impl MapBuilder {
+    pub fn new(
+        rng: &mut RandomNumberGenerator,
+        architect: Option<ArchitectType>,
+        dimensions: Option<Point>,
+        topology: Topology
+    ) -> Self {
+        let architect_type = architect.unwrap_or_else(|| ArchitectType::random(rng));
+        let dimensions = dimensions.unwrap_or_else(|| architect_type.default_dimensions());
+        let mut architect: Box<dyn MapArchitect> = match architect_type {
+            ArchitectType::Rooms => Box::new(RoomsArchitect {}),
+            ArchitectType::Caves => Box::new(CellularAutomataArchitect {}),
+            ArchitectType::Drunkard => Box::new(DrunkardsWalkArchitect {}),
+            ArchitectType::Bsp => Box::new(BspArchitect {}),
+        };
+        let mut mb = architect.build(rng, dimensions.x, dimensions.y);
+        // Architects carve assuming diagonal steps; without them some pockets may be cut off
+        mb.map.topology = topology;
+        mb.prune_unreachable();
+        mb.stairs = mb.find_most_distant();
+        let stairs_idx = mb.map.point2d_to_index(mb.stairs);
+        mb.map.tiles[stairs_idx] = TileType::DownStairs;
+        mb
+    }
+
+    fn empty(width: i32, height: i32) -> Self {
+        MapBuilder{
+            map : Map::new(width, height),
+            rooms : Vec::new(),
+            player_start : Point::zero(),
+            stairs : Point::zero()
+        }
+    }
+
+    fn fill(&mut self, tile : TileType) {
+        self.map.tiles.iter_mut().for_each(|t| *t = tile);
+    }
+
+    // Walking distance from the player's start to every tile, f32::MAX where there's no way.
+    // No depth cap, so far corners of a big map still count as reachable
+    fn distances_from_start(&self) -> DijkstraMap {
+        DijkstraMap::new(
+            self.map.width,
+            self.map.height,
+            &[self.map.point2d_to_index(self.player_start)],
+            &self.map,
+            f32::MAX
+        )
+    }
+
+    fn find_most_distant(&self) -> Point {
+        const UNREACHABLE : &f32 = &f32::MAX;
+        self.map.index_to_point2d(
+            self.distances_from_start().map
+                .iter()
+                .enumerate()
+                .filter(|(_, dist)| *dist < UNREACHABLE)
+                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+                .unwrap().0
+        )
+    }
+
+    fn find_start_near_center(&self) -> Point {
+        let center = Point::new(self.map.width / 2, self.map.height / 2);
+        let closest_point = self.map.tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| **t == TileType::Floor)
+            .map(|(idx, _)| (idx, DistanceAlg::Pythagoras.distance2d(
+                center,
+                self.map.index_to_point2d(idx)
+            )))
+            .min_by(|(_, distance), (_, distance2)|
+                distance.partial_cmp(distance2).unwrap()
+            )
+            .map(|(idx, _)| idx)
+            .unwrap();
+        self.map.index_to_point2d(closest_point)
+    }
+
+    // Walls off any floor the player can't walk to, so nothing spawns out of reach
+    fn prune_unreachable(&mut self) {
+        self.distances_from_start().map
+            .iter()
+            .enumerate()
+            .filter(|(_, distance)| **distance >= f32::MAX)
+            .for_each(|(idx, _)| self.map.tiles[idx] = TileType::Wall);
+    }
+
+    fn build_random_rooms(&mut self, rng : &mut RandomNumberGenerator, num_rooms: usize) {
+        // Small maps can fill up before reaching num_rooms, so give up eventually
+        let mut attempts = 0;
+        while self.rooms.len() < num_rooms && attempts < num_rooms * 100 {
+            attempts += 1;
+            let room = Rect::with_size(
+                rng.range(1, self.map.width - 10),
+                rng.range(1, self.map.height - 10),
+                rng.range(2, 10),
+                rng.range(2, 10),
+            );
+            let mut overlap = false;
+            for r in self.rooms.iter() {
+                if r.intersect(&room) {
+                    overlap = true;
+                }
+            }
+            if !overlap {
+                self.carve_room(&room);
+                self.rooms.push(room)
+            }
+        }
+    }
+
+    fn carve_room(&mut self, room: &Rect) {
+        room.for_each(|p| {
+            if p.x > 0 && p.x < self.map.width && p.y > 0 && p.y < self.map.height {
+                let idx = self.map.map_idx(p.x, p.y);
+                self.map.tiles[idx] = TileType::Floor;
+            }
+        });
+    }
+
+    fn apply_horizontal_tunnel(&mut self, x1:i32, x2:i32, y:i32) {
+        use std::cmp::{min, max};
+        for x in min(x1,x2) ..= max(x1,x2) {
+            if let Some(idx) = self.map.try_idx(Point::new(x, y)) {
+                self.map.tiles[idx as usize] = TileType::Floor;
+            }
+        }
+    }
+
+    fn apply_vertical_tunnel(&mut self, y1:i32, y2:i32, x:i32) {
+        use std::cmp::{min, max};
+        for y in min(y1,y2) ..= max(y1,y2) {
+            if let Some(idx) = self.map.try_idx(Point::new(x, y)) {
+                self.map.tiles[idx as usize] = TileType::Floor;
+            }
+        }
+    }
+
+    fn connect_points(&mut self, rng: &mut RandomNumberGenerator, from: Point, to: Point) {
+        if rng.range(0,2) == 1 {
+            self.apply_horizontal_tunnel(from.x, to.x, from.y);
+            self.apply_vertical_tunnel(from.y, to.y, to.x);
+        } else {
+            self.apply_vertical_tunnel(from.y, to.y, from.x);
+            self.apply_horizontal_tunnel(from.x, to.x, to.y);
+        }
+    }
+
+    fn build_corridors(&mut self, rng: &mut RandomNumberGenerator) {
+        let mut rooms = self.rooms.clone();
+        rooms.sort_by(|a,b| a.center().x.cmp(&b.center().x));
+
+        for (i, room) in rooms.iter().enumerate().skip(1) {
+            let prev = rooms[i-1].center();
+            let new = room.center();
+            self.connect_points(rng, prev, new);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One corridor winding back and forth across the map, far longer than 1024 steps
+    fn serpentine(width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::empty(width, height);
+        mb.fill(TileType::Wall);
+        for y in (1 .. height - 1).step_by(2) {
+            for x in 1 .. width - 1 {
+                let idx = mb.map.map_idx(x, y);
+                mb.map.tiles[idx] = TileType::Floor;
+            }
+            // Joined to the next row at alternate ends
+            if y + 2 < height - 1 {
+                let x = if y % 4 == 1 { width - 2 } else { 1 };
+                let idx = mb.map.map_idx(x, y + 1);
+                mb.map.tiles[idx] = TileType::Floor;
+            }
+        }
+        mb.map.topology = Topology::FourWay;
+        mb.player_start = Point::new(1, 1);
+        mb
+    }
+
+    #[test]
+    fn pruning_keeps_floor_at_the_far_end_of_a_long_walk() {
+        let mut mb = serpentine(60, 61);
+        let floor = mb.map.tiles.iter().filter(|tile| **tile == TileType::Floor).count();
+        assert!(floor > 1500);
+
+        mb.prune_unreachable();
+        assert_eq!(mb.map.tiles.iter().filter(|tile| **tile == TileType::Floor).count(), floor);
+        assert_eq!(mb.find_most_distant(), Point::new(1, 59));
+    }
+
+    #[test]
+    fn pruning_walls_off_floor_with_no_way_there() {
+        let mut mb = serpentine(20, 9);
+        let cut_off = mb.map.map_idx(18, 2);
+        mb.map.tiles[cut_off] = TileType::Wall;
+        mb.prune_unreachable();
+        assert!(mb.map.tiles[mb.map.map_idx(1, 1)] == TileType::Floor);
+        assert!(mb.map.tiles.iter().skip(mb.map.map_idx(0, 3)).all(|tile| *tile == TileType::Wall));
+    }
+}
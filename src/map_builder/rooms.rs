@@ -0,0 +1,19 @@
+use crate::prelude::*;
+use super::MapArchitect;
+
+// One room per this many tiles, i.e. 20 rooms on an 80x50 map
+const TILES_PER_ROOM: i32 = 200;
+
+pub struct RoomsArchitect {}
+
+impl MapArchitect for RoomsArchitect {
+    fn build(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::empty(width, height);
+        mb.fill(TileType::Wall);
+        let num_rooms = (width * height / TILES_PER_ROOM).max(1) as usize;
+        mb.build_random_rooms(rng, num_rooms);
+        mb.build_corridors(rng);
+        mb.player_start = mb.rooms[0].center();
+        mb
+    }
+}
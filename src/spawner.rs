@@ -1,7 +1,7 @@
 use crate::prelude::*;
 
 pub fn spawn_player(ecs : &mut World, pos : Point) {
-    ecs.push(
+    let player = ecs.push(
         (Player, 
             pos,
             Render{
@@ -10,20 +10,35 @@ pub fn spawn_player(ecs : &mut World, pos : Point) {
             },
             Health{ current: 15, max: 15 },
             Mana{ current: 8, max: 8 },
-            CanDash{ cost: 4, range: 4 },
-            CanCastFireball{ cost: 5, damage: 3, range: 6 },
+            CombatStats{ power: 3, defense: 0, accuracy: 90, evasion: 10 },
+            FieldOfView::new(8),
+            Name("Player".to_string()),
         )
     );
+
+    // Legion caps a single push at eight components, so abilities, experience and energy go on afterwards
+    if let Some(mut entry) = ecs.entry(player) {
+        entry.add_component(Abilities(load_abilities()));
+        entry.add_component(Experience::new());
+        // Ready to move straight away
+        entry.add_component(Energy { speed: 100, current: Energy::TURN });
+    }
 }
 
-pub fn spawn_wave_monsters(ecs: &mut World, map: &Map, wave_number: i32) -> i32 {
-    let mut rng = RandomNumberGenerator::new();
+pub fn spawn_wave_monsters(
+    ecs: &mut World,
+    rng: &mut RandomNumberGenerator,
+    map: &Map,
+    wave_number: i32,
+    level: i32,
+    game_log: &mut GameLog
+) -> i32 {
     let mut enemies_spawned = 0;
     
     // Find valid spawn positions
     let mut spawn_positions = Vec::new();
-    for y in 1..SCREEN_HEIGHT-1 {
-        for x in 1..SCREEN_WIDTH-1 {
+    for y in 1..map.height-1 {
+        for x in 1..map.width-1 {
             let pos = Point::new(x, y);
             if map.can_enter_tile(pos) {
                 spawn_positions.push(pos);
@@ -39,11 +54,25 @@ pub fn spawn_wave_monsters(ecs: &mut World, map: &Map, wave_number: i32) -> i32
     
     let mut spawn_index = 0;
     
+    // Every floor below the first adds one more random enemy to each wave
+    for _ in 1..level {
+        if spawn_index < spawn_positions.len() {
+            let enemy_type = match rng.range(0, 3) {
+                0 => EnemyType::Weak,
+                1 => EnemyType::Medium,
+                _ => EnemyType::Boss,
+            };
+            spawn_monster_by_type(ecs, enemy_type, spawn_positions[spawn_index], level);
+            spawn_index += 1;
+            enemies_spawned += 1;
+        }
+    }
+    
     match wave_number {
         1 => {
             for _ in 0..3 {
                 if spawn_index < spawn_positions.len() {
-                    spawn_monster_by_type(ecs, EnemyType::Weak, spawn_positions[spawn_index]);
+                    spawn_monster_by_type(ecs, EnemyType::Weak, spawn_positions[spawn_index], level);
                     spawn_index += 1;
                     enemies_spawned += 1;
                 }
@@ -52,14 +81,14 @@ pub fn spawn_wave_monsters(ecs: &mut World, map: &Map, wave_number: i32) -> i32
         2 => {
             for _ in 0..2 {
                 if spawn_index < spawn_positions.len() {
-                    spawn_monster_by_type(ecs, EnemyType::Weak, spawn_positions[spawn_index]);
+                    spawn_monster_by_type(ecs, EnemyType::Weak, spawn_positions[spawn_index], level);
                     spawn_index += 1;
                     enemies_spawned += 1;
                 }
             }
             for _ in 0..2 {
                 if spawn_index < spawn_positions.len() {
-                    spawn_monster_by_type(ecs, EnemyType::Medium, spawn_positions[spawn_index]);
+                    spawn_monster_by_type(ecs, EnemyType::Medium, spawn_positions[spawn_index], level);
                     spawn_index += 1;
                     enemies_spawned += 1;
                 }
@@ -68,13 +97,13 @@ pub fn spawn_wave_monsters(ecs: &mut World, map: &Map, wave_number: i32) -> i32
         3 => {
             for _ in 0..3 {
                 if spawn_index < spawn_positions.len() {
-                    spawn_monster_by_type(ecs, EnemyType::Medium, spawn_positions[spawn_index]);
+                    spawn_monster_by_type(ecs, EnemyType::Medium, spawn_positions[spawn_index], level);
                     spawn_index += 1;
                     enemies_spawned += 1;
                 }
             }
             if spawn_index < spawn_positions.len() {
-                spawn_monster_by_type(ecs, EnemyType::Boss, spawn_positions[spawn_index]);
+                spawn_monster_by_type(ecs, EnemyType::Boss, spawn_positions[spawn_index], level);
                 enemies_spawned += 1;
             }
         }
@@ -86,7 +115,7 @@ pub fn spawn_wave_monsters(ecs: &mut World, map: &Map, wave_number: i32) -> i32
                         1 => EnemyType::Medium,
                         _ => EnemyType::Boss,
                     };
-                    spawn_monster_by_type(ecs, enemy_type, spawn_positions[spawn_index]);
+                    spawn_monster_by_type(ecs, enemy_type, spawn_positions[spawn_index], level);
                     spawn_index += 1;
                     enemies_spawned += 1;
                 }
@@ -94,27 +123,173 @@ pub fn spawn_wave_monsters(ecs: &mut World, map: &Map, wave_number: i32) -> i32
         }
     }
     
+    game_log.add(
+        LogCategory::Wave,
+        format!("Wave {} begins: {} enemies approach", wave_number, enemies_spawned)
+    );
     enemies_spawned
 }
 
-fn spawn_monster_by_type(ecs: &mut World, enemy_type: EnemyType, pos: Point) {
-    let (hp, name, glyph, color) = match enemy_type {
+fn spawn_monster_by_type(ecs: &mut World, enemy_type: EnemyType, pos: Point, level: i32) {
+    let (base_hp, name, glyph, color) = match enemy_type {
         EnemyType::Weak => (2, "Goblin".to_string(), to_cp437('g'), ColorPair::new(WHITE, BLACK)),
         EnemyType::Medium => (5, "Orc".to_string(), to_cp437('O'), ColorPair::new(WHITE, BLACK)),
         EnemyType::Boss => (12, "Troll".to_string(), to_cp437('E'), ColorPair::new(WHITE, BLACK)),
     };
+    // Goblins are quick but feeble, orcs are all-rounders, trolls hit hard and shrug off blows
+    let stats = match enemy_type {
+        EnemyType::Weak => CombatStats{ power: 1, defense: 0, accuracy: 70, evasion: 25 },
+        EnemyType::Medium => CombatStats{ power: 2, defense: 1, accuracy: 75, evasion: 10 },
+        EnemyType::Boss => CombatStats{ power: 4, defense: 2, accuracy: 60, evasion: 0 },
+    };
+    // Deeper floors breed tougher monsters: +50% health per floor
+    let hp = base_hp + base_hp * (level - 1) / 2;
+    // Goblins roam and scatter when hurt, orcs throw spears from a distance, trolls stand guard
+    let behaviour = match enemy_type {
+        EnemyType::Weak => Behaviour::Wanderer,
+        EnemyType::Medium => Behaviour::Ranged { range: 4 },
+        EnemyType::Boss => Behaviour::Guard { post: pos, radius: 5 },
+    };
 
-    ecs.push(
+    let monster = ecs.push(
         (Enemy,
             pos,
             Render{
                 color,
                 glyph,
             },
-            FollowsPlayer{ move_timer: 0 },
+            Energy::new(enemy_type.speed()),
             Health{current: hp, max: hp},
+            stats,
             Name(name),
             EnemyStats{ enemy_type },
         )
     );
-}
\ No newline at end of file
+
+    // Already eight components, the most a single push takes
+    if let Some(mut entry) = ecs.entry(monster) {
+        entry.add_component(behaviour);
+        entry.add_component(Awareness { sight: enemy_type.sight(), state: Alertness::Unaware });
+        if enemy_type == EnemyType::Weak {
+            entry.add_component(FleesBelow(hp / 2));
+        }
+    }
+}
+
+// A ghostly copy of the caster that fights on its side for a few turns
+pub fn spawn_summon(commands: &mut CommandBuffer, owner: Entity, pos: Point, name: &str, turns: i32, health: i32, power: i32) {
+    commands.push(
+        (Ally{ owner, turns },
+            pos,
+            Render{
+                color: ColorPair::new(CYAN, BLACK),
+                glyph: to_cp437('@')
+            },
+            Health{ current: health, max: health },
+            CombatStats{ power, defense: 0, accuracy: 80, evasion: 10 },
+            Name(name.to_string()),
+            Energy::new(100),
+        )
+    );
+}
+
+pub fn spawn_item(commands: &mut CommandBuffer, item_type: ItemType, pos: Point) {
+    let (name, glyph, color) = match item_type {
+        ItemType::HealingPotion => ("Healing Potion", to_cp437('!'), ColorPair::new(WHITE, BLACK)),
+        ItemType::ManaPotion => ("Mana Potion", to_cp437('!'), ColorPair::new(CYAN, BLACK)),
+        ItemType::FireballScroll => ("Scroll of Fireball", to_cp437('{'), ColorPair::new(WHITE, BLACK)),
+        ItemType::RustyDagger => ("Rusty Dagger", to_cp437('s'), ColorPair::new(WHITE, BLACK)),
+        ItemType::ShinySword => ("Shiny Sword", to_cp437('S'), ColorPair::new(WHITE, BLACK)),
+        // The font has no armour sprite, so worn gear shares the charm glyph with a tint
+        ItemType::LeatherArmor => ("Leather Armor", to_cp437('|'), ColorPair::new(BURLYWOOD, BLACK)),
+        ItemType::ChainMail => ("Chain Mail", to_cp437('|'), ColorPair::new(LIGHT_GRAY, BLACK)),
+        ItemType::AmuletOfMana => ("Amulet of Mana", to_cp437('|'), ColorPair::new(CYAN, BLACK)),
+        ItemType::EmberRing => ("Ember Ring", to_cp437('|'), ColorPair::new(ORANGE, BLACK)),
+        ItemType::FeatherCharm => ("Feather Charm", to_cp437('|'), ColorPair::new(GREEN, BLACK)),
+    };
+
+    let item = commands.push(
+        (Item,
+            pos,
+            Render{
+                color,
+                glyph,
+            },
+            Name(name.to_string()),
+        )
+    );
+
+    match item_type {
+        ItemType::HealingPotion => commands.add_component(item, ProvidesHealing{ amount: 6 }),
+        ItemType::ManaPotion => commands.add_component(item, ProvidesMana{ amount: 5 }),
+        ItemType::FireballScroll => commands.add_component(item, ProvidesSpell("Fireball".to_string())),
+        ItemType::RustyDagger => equipment(commands, item, EquipmentSlot::Weapon, EquipmentBonus {
+            power: 1, ..Default::default()
+        }),
+        ItemType::ShinySword => equipment(commands, item, EquipmentSlot::Weapon, EquipmentBonus {
+            power: 3, ..Default::default()
+        }),
+        ItemType::LeatherArmor => equipment(commands, item, EquipmentSlot::Armor, EquipmentBonus {
+            defense: 1, ..Default::default()
+        }),
+        // Heavy mail gets in the way of spellcasting
+        ItemType::ChainMail => equipment(commands, item, EquipmentSlot::Armor, EquipmentBonus {
+            defense: 3, max_mana: -2, ..Default::default()
+        }),
+        ItemType::AmuletOfMana => equipment(commands, item, EquipmentSlot::Trinket, EquipmentBonus {
+            max_mana: 5, ..Default::default()
+        }),
+        ItemType::EmberRing => equipment(commands, item, EquipmentSlot::Trinket, EquipmentBonus {
+            spell_damage: 2, ..Default::default()
+        }),
+        ItemType::FeatherCharm => equipment(commands, item, EquipmentSlot::Trinket, EquipmentBonus {
+            dash_range: 2, ..Default::default()
+        }),
+    }
+}
+
+fn equipment(commands: &mut CommandBuffer, item: Entity, slot: EquipmentSlot, bonus: EquipmentBonus) {
+    commands.add_component(item, Equippable{ slot });
+    commands.add_component(item, bonus);
+}
+
+pub fn spawn_level_items(
+    ecs: &mut World,
+    rng: &mut RandomNumberGenerator,
+    map: &Map,
+    rooms: &[Rect],
+    player_start: Point
+) {
+    let mut spots = Vec::new();
+    if rooms.is_empty() {
+        // Caves and tunnels have no rooms, so scatter items over the open floor
+        let floor: Vec<Point> = (0..map.tiles.len())
+            .map(|idx| map.index_to_point2d(idx))
+            .filter(|pos| map.tiles[map.point2d_to_index(*pos)] == TileType::Floor && *pos != player_start)
+            .collect();
+        for _ in 0..floor.len() / 250 {
+            if let Some(pos) = rng.random_slice_entry(&floor) {
+                spots.push(*pos);
+            }
+        }
+    } else {
+        // Roughly every third room holds something, never the one the player starts in
+        for room in rooms.iter().filter(|room| !room.point_in_rect(player_start)) {
+            if rng.range(0, 3) == 0 {
+                let pos = Point::new(
+                    rng.range(room.x1, room.x2),
+                    rng.range(room.y1, room.y2)
+                );
+                if map.try_idx(pos).map_or(false, |idx| map.tiles[idx] == TileType::Floor) {
+                    spots.push(pos);
+                }
+            }
+        }
+    }
+
+    let mut commands = CommandBuffer::new(ecs);
+    for pos in spots {
+        spawn_item(&mut commands, ItemType::random(rng), pos);
+    }
+    commands.flush(ecs);
+}
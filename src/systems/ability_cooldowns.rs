@@ -0,0 +1,21 @@
+use crate::prelude::*;
+
+// Goes by the clock rather than the player's turns, so a hasted player doesn't recharge faster
+#[system]
+#[read_component(Player)]
+#[read_component(Abilities)]
+#[read_component(TimePassed)]
+pub fn ability_cooldowns(ecs: &SubWorld, commands: &mut CommandBuffer) {
+    let ticks: i32 = <&TimePassed>::query().iter(ecs).map(|time| time.ticks).sum();
+    let mut player_query = <(Entity, &Abilities)>::query().filter(component::<Player>());
+
+    if let Some((player_entity, abilities)) = player_query.iter(ecs).nth(0) {
+        if ticks > 0 && abilities.0.iter().any(|ability| ability.charges < ability.max_charges) {
+            let mut abilities = abilities.clone();
+            for _ in 0..ticks {
+                abilities.0.iter_mut().for_each(Ability::tick_cooldown);
+            }
+            commands.add_component(*player_entity, abilities);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::prelude::*;
+
+// How far a summoned ally looks for something to fight
+const ALLY_SIGHT: f32 = 8.0;
+// Without a fight, allies drift back to stay this close to their owner
+const ALLY_LEASH: f32 = 2.0;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Ally)]
+#[read_component(Enemy)]
+#[read_component(Player)]
+#[read_component(Name)]
+#[read_component(MyTurn)]
+pub fn allies(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] map: &Map,
+    #[resource] game_log: &mut GameLog
+) {
+    let enemies: Vec<(Entity, Point)> = <(Entity, &Point)>::query()
+        .filter(component::<Enemy>())
+        .iter(ecs)
+        .map(|(entity, pos)| (*entity, *pos))
+        .collect();
+    // Nobody steps onto anybody else
+    let mut occupied: Vec<Point> = <&Point>::query()
+        .filter(component::<Enemy>() | component::<Ally>() | component::<Player>())
+        .iter(ecs)
+        .copied()
+        .collect();
+
+    let mut allies = <(Entity, &Point, &Ally, &Name)>::query().filter(component::<MyTurn>());
+    allies.iter(ecs).for_each(|(entity, pos, ally, name)| {
+        if ally.turns <= 1 {
+            commands.remove(*entity);
+            game_log.add(LogCategory::Spell, format!("The {} fades away", name.0));
+            return;
+        }
+        commands.add_component(*entity, Ally { turns: ally.turns - 1, ..*ally });
+
+        let target = enemies
+            .iter()
+            .filter(|(_, enemy_pos)| DistanceAlg::Pythagoras.distance2d(*pos, *enemy_pos) <= ALLY_SIGHT)
+            .filter(|(_, enemy_pos)| map.has_line_of_sight(*pos, *enemy_pos))
+            .min_by_key(|(_, enemy_pos)| DistanceAlg::PythagorasSquared.distance2d(*pos, *enemy_pos) as i32);
+
+        let destination = match target {
+            Some((enemy, enemy_pos)) => {
+                if map.topology.is_adjacent(*pos, *enemy_pos) {
+                    commands.push(((), WantsToAttack {
+                        attacker: *entity,
+                        victim: *enemy,
+                    }));
+                    None
+                } else {
+                    step_towards(map, &occupied, *pos, *enemy_pos, 0.0)
+                }
+            }
+            None => ecs.entry_ref(ally.owner).ok()
+                .and_then(|owner| owner.get_component::<Point>().ok().copied())
+                .and_then(|owner_pos| step_towards(map, &occupied, *pos, owner_pos, ALLY_LEASH)),
+        };
+
+        if let Some(destination) = destination {
+            occupied.retain(|tile| tile != pos);
+            occupied.push(destination);
+            commands.push(((), WantsToMove {
+                entity: *entity,
+                destination,
+            }));
+        }
+    });
+}
+
+// The free neighbouring tile that closes the most distance, unless already within `close_enough`
+fn step_towards(map: &Map, occupied: &[Point], from: Point, to: Point, close_enough: f32) -> Option<Point> {
+    let current = DistanceAlg::Pythagoras.distance2d(from, to);
+    if current <= close_enough {
+        return None;
+    }
+
+    let mut best = None;
+    let mut best_distance = current;
+    for step in map.topology.steps() {
+        let next = from + *step;
+        if !map.can_enter_tile(next) || occupied.contains(&next) {
+            continue;
+        }
+        let distance = DistanceAlg::Pythagoras.distance2d(next, to);
+        if distance < best_distance {
+            best_distance = distance;
+            best = Some(next);
+        }
+    }
+    best
+}
@@ -0,0 +1,156 @@
+use crate::prelude::*;
+use legion::world::EntryRef;
+
+#[system(for_each)]
+#[read_component(Abilities)]
+#[read_component(Mana)]
+#[read_component(Point)]
+#[read_component(FieldOfView)]
+#[read_component(Player)]
+#[read_component(Health)]
+#[read_component(Enemy)]
+#[read_component(Name)]
+#[read_component(EnemyStats)]
+#[read_component(Ally)]
+pub fn cast_ability(
+    entity: &Entity,
+    want_cast: &WantsToCast,
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] map: &Map,
+    #[resource] camera: &mut Camera,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] game_log: &mut GameLog
+) {
+    let creatures = Creatures::gather(ecs);
+    if let Ok(entry) = ecs.entry_ref(want_cast.caster) {
+        if let (Ok(abilities), Ok(mana), Ok(caster_pos)) = (
+            entry.get_component::<Abilities>(),
+            entry.get_component::<Mana>(),
+            entry.get_component::<Point>()
+        ) {
+            if let Some(ability) = abilities.0.get(want_cast.ability) {
+                // Scrolls carry their own power: no mana cost, and the reader already picked a visible target
+                let cost = if want_cast.from_scroll { 0 } else { ability.cost };
+                let valid = want_cast.from_scroll || ability.valid_target(map, &creatures, *caster_pos, want_cast.target);
+                let ready = want_cast.from_scroll || ability.is_ready();
+
+                if mana.current >= cost && valid && ready {
+                    if cost > 0 {
+                        commands.add_component(want_cast.caster, Mana {
+                            current: mana.current - cost,
+                            max: mana.max,
+                        });
+                    }
+                    if !want_cast.from_scroll {
+                        let mut abilities = abilities.clone();
+                        abilities.0[want_cast.ability].spend_charge();
+                        commands.add_component(want_cast.caster, abilities);
+                    }
+                    game_log.add(LogCategory::Spell, format!("Player casts {}", ability.name));
+
+                    match &ability.effect {
+                        AbilityEffect::Teleport => move_caster(&entry, commands, camera, want_cast.caster, want_cast.target),
+                        AbilityEffect::Blink => {
+                            let destinations = ability.blink_destinations(map, &creatures, *caster_pos);
+                            if destinations.is_empty() {
+                                game_log.add(LogCategory::Spell, "The air shimmers, but there is nowhere to go");
+                            } else {
+                                let destination = destinations[rng.range(0, destinations.len())];
+                                move_caster(&entry, commands, camera, want_cast.caster, destination);
+                            }
+                        }
+                        AbilityEffect::Damage { amount, inflicts } => {
+                            let area = ability.area(map, &creatures, *caster_pos, want_cast.target);
+                            commands.push((SpellEffect {
+                                tiles: area.clone(),
+                                color: ability.color,
+                            },));
+                            damage_area(ecs, commands, game_log, want_cast, ability, &area, *amount, *inflicts);
+                        }
+                        AbilityEffect::Heal { amount } => {
+                            if let Ok(health) = entry.get_component::<Health>() {
+                                let healed = (*amount).min(health.max - health.current);
+                                commands.add_component(want_cast.caster, Health {
+                                    current: health.current + healed,
+                                    max: health.max,
+                                });
+                                game_log.add(LogCategory::Spell, format!("Player recovers {} health", healed));
+                            }
+                        }
+                        AbilityEffect::Empower { grants } => {
+                            grants.effect.apply(commands, want_cast.caster, grants.turns, grants.magnitude);
+                            game_log.add(LogCategory::Status, format!("Player is {}", grants.effect.name().to_lowercase()));
+                        }
+                        AbilityEffect::Summon { name, turns, health, power } => {
+                            spawn_summon(commands, want_cast.caster, want_cast.target, name, *turns, *health, *power);
+                            game_log.add(LogCategory::Spell, format!("A {} answers the call", name));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    commands.remove(*entity);
+}
+
+fn move_caster(entry: &EntryRef, commands: &mut CommandBuffer, camera: &mut Camera, caster: Entity, destination: Point) {
+    commands.add_component(caster, destination);
+    if let Ok(fov) = entry.get_component::<FieldOfView>() {
+        commands.add_component(caster, fov.clone_dirty());
+    }
+    if entry.get_component::<Player>().is_ok() {
+        camera.on_player_move(destination);
+    }
+}
+
+fn damage_area(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    game_log: &mut GameLog,
+    want_cast: &WantsToCast,
+    ability: &Ability,
+    area: &[Point],
+    amount: i32,
+    inflicts: Option<Affliction>
+) {
+    let victims: Vec<(Entity, Health, String, EnemyType)> = <(Entity, &Point, &Health, &Name, &EnemyStats)>::query()
+        .filter(component::<Enemy>())
+        .iter(ecs)
+        .filter(|(_, pos, ..)| area.contains(pos))
+        .map(|(enemy, _, health, name, stats)| (*enemy, *health, name.0.clone(), stats.enemy_type))
+        .collect();
+
+    game_log.add(
+        LogCategory::Spell,
+        format!("{} engulfs {} {}", ability.name, victims.len(),
+            if victims.len() == 1 { "enemy" } else { "enemies" })
+    );
+
+    let mut xp = 0;
+    for (enemy, health, name, enemy_type) in victims {
+        let new_health = Health {
+            current: (health.current - amount).max(0),
+            max: health.max,
+        };
+
+        if new_health.current <= 0 {
+            commands.remove(enemy);
+            xp += enemy_type.xp();
+            game_log.add(LogCategory::Kill, format!("{} is destroyed by the {}", name, ability.name));
+        } else {
+            commands.add_component(enemy, new_health);
+            if let Some(affliction) = inflicts {
+                affliction.effect.apply(commands, enemy, affliction.turns, affliction.magnitude);
+                game_log.add(LogCategory::Status, format!("{} is {}", name, affliction.effect.name().to_lowercase()));
+            }
+        }
+    }
+
+    if xp > 0 {
+        commands.push(((), GainExperience {
+            entity: want_cast.caster,
+            amount: xp,
+        }));
+    }
+}
@@ -1,5 +1,7 @@
 use crate::prelude::*;
 
+// Only a safety net for an enemy ending up on the player's own tile; ordinary attacks on
+// adjacent tiles are decided in pathfinding using the map's topology
 #[system]
 #[read_component(Point)]
 #[read_component(Player)]
@@ -1,30 +1,82 @@
 use crate::prelude::*;
 
+// Every attack keeps at least this much chance to land or miss
+const MIN_HIT_CHANCE: i32 = 5;
+const MAX_HIT_CHANCE: i32 = 95;
+
 #[system(for_each)]
 #[read_component(Health)]
+#[read_component(CombatStats)]
 #[read_component(Player)]
 #[read_component(Name)]
+#[read_component(Equipped)]
+#[read_component(EquipmentBonus)]
+#[read_component(EnemyStats)]
+#[read_component(Ally)]
 pub fn combat(
     entity: &Entity,
     want_attack: &WantsToAttack,
     ecs: &mut SubWorld,
-    commands: &mut CommandBuffer
+    commands: &mut CommandBuffer,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] game_log: &mut GameLog
 ) {
-    if let Ok(victim_entry) = ecs.entry_ref(want_attack.victim) {
-        if let Ok(victim_health) = victim_entry.get_component::<Health>() {
-            let damage = 1; // Everyone does 1 damage
-            
-            let new_health = Health {
-                current: (victim_health.current - damage).max(0),
-                max: victim_health.max,
-            };
+    let (attacker_stats, attacker_name) = match ecs.entry_ref(want_attack.attacker) {
+        Ok(entry) => (
+            entry.get_component::<CombatStats>().ok().copied(),
+            entry.get_component::<Name>().map_or_else(|_| "Something".to_string(), |name| name.0.clone())
+        ),
+        Err(_) => (None, String::new()),
+    };
+
+    if let (Ok(victim_entry), Some(attacker_stats)) = (ecs.entry_ref(want_attack.victim), attacker_stats) {
+        if let (Ok(victim_health), Ok(victim_stats)) = (
+            victim_entry.get_component::<Health>(),
+            victim_entry.get_component::<CombatStats>()
+        ) {
+            let victim_name = victim_entry.get_component::<Name>()
+                .map_or_else(|_| "something".to_string(), |name| name.0.clone());
+            let hit_chance = (attacker_stats.accuracy - victim_stats.evasion)
+                .clamp(MIN_HIT_CHANCE, MAX_HIT_CHANCE);
             
-            if new_health.current <= 0 {
-                commands.remove(want_attack.victim);
+            if rng.range(0, 100) < hit_chance {
+                // A hit always hurts, however thick the armour
+                let power = attacker_stats.power + EquipmentBonus::worn_by(ecs, want_attack.attacker).power;
+                let defense = victim_stats.defense + EquipmentBonus::worn_by(ecs, want_attack.victim).defense;
+                let damage = (power - defense).max(1);
+                
+                let new_health = Health {
+                    current: (victim_health.current - damage).max(0),
+                    max: victim_health.max,
+                };
+                
+                game_log.add(
+                    LogCategory::Combat,
+                    format!("{} hits {} for {} damage", attacker_name, victim_name, damage)
+                );
+                
+                // The player is never removed; end_turn sees the empty health bar and ends the game
+                let is_player = victim_entry.get_component::<Player>().is_ok();
+                if new_health.current <= 0 && !is_player {
+                    commands.remove(want_attack.victim);
+                    game_log.add(LogCategory::Kill, format!("{} kills {}", attacker_name, victim_name));
+                    if let Ok(enemy_stats) = victim_entry.get_component::<EnemyStats>() {
+                        // Summons earn experience for whoever called them
+                        let earner = ecs.entry_ref(want_attack.attacker).ok()
+                            .and_then(|entry| entry.get_component::<Ally>().ok().map(|ally| ally.owner))
+                            .unwrap_or(want_attack.attacker);
+                        commands.push(((), GainExperience {
+                            entity: earner,
+                            amount: enemy_stats.enemy_type.xp(),
+                        }));
+                    }
+                } else {
+                    commands.add_component(want_attack.victim, new_health);
+                }
             } else {
-                commands.add_component(want_attack.victim, new_health);
+                game_log.add(LogCategory::Combat, format!("{} misses {}", attacker_name, victim_name));
             }
         }
     }
     commands.remove(*entity);
-}
\ No newline at end of file
+}
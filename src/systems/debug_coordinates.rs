@@ -47,23 +47,21 @@ pub fn debug_coordinates(
             format!("Valid Tile: {}, Can Enter: {}", is_valid_tile, can_enter)
         );
         
-        if *targeting_state == TargetingState::SelectingFireballTarget {
-            let has_los = has_line_of_sight(map, *player_pos, world_mouse);
-            let distance = DistanceAlg::Pythagoras.distance2d(*player_pos, world_mouse);
-            
-            draw_batch.print(
-                Point::new(1, 19),
-                format!("Line of Sight: {}", has_los)
-            );
-            draw_batch.print(
-                Point::new(1, 20),
-                format!("Distance: {:.2}", distance)
-            );
-        }
+        let has_los = map.has_line_of_sight(*player_pos, world_mouse);
+        let distance = DistanceAlg::Pythagoras.distance2d(*player_pos, world_mouse);
+        
+        draw_batch.print(
+            Point::new(1, 19),
+            format!("Line of Sight: {}", has_los)
+        );
+        draw_batch.print(
+            Point::new(1, 20),
+            format!("Distance: {:.2}", distance)
+        );
         
         draw_batch.print(
             Point::new(1, 21),
-            format!("Display: {}x{} | Screen: {}x{}", DISPLAY_WIDTH, DISPLAY_HEIGHT, SCREEN_WIDTH, SCREEN_HEIGHT)
+            format!("Display: {}x{} | Map: {}x{}", DISPLAY_WIDTH, DISPLAY_HEIGHT, map.width, map.height)
         );
         
         let at_edge = mouse_pos.x == 0 || mouse_pos.x == DISPLAY_WIDTH - 1 || 
@@ -83,38 +81,3 @@ pub fn debug_coordinates(
         draw_batch.submit(11000).expect("Batch error");
     }
 }
-
-fn has_line_of_sight(map: &Map, start: Point, end: Point) -> bool {
-    let mut current = start;
-    let dx = (end.x - start.x).abs();
-    let dy = (end.y - start.y).abs();
-    let sx = if start.x < end.x { 1 } else { -1 };
-    let sy = if start.y < end.y { 1 } else { -1 };
-    let mut err = dx - dy;
-
-    loop {
-        if current != start {
-            if current == end {
-                return true;
-            }
-            
-            if !map.can_enter_tile(current) {
-                return false;
-            }
-        }
-
-        let e2 = 2 * err;
-        if e2 > -dy {
-            err -= dy;
-            current.x += sx;
-        }
-        if e2 < dx {
-            err += dx;
-            current.y += sy;
-        }
-
-        if current == end {
-            return true;
-        }
-    }
-}
\ No newline at end of file
@@ -1,12 +1,56 @@
 use crate::prelude::*;
 
 #[system]
-pub fn end_turn(#[resource] turn_state: &mut TurnState) {
-    let new_state = match turn_state {
-        TurnState::AwaitingInput => return,
+#[read_component(Point)]
+#[read_component(Health)]
+#[read_component(Player)]
+#[read_component(Experience)]
+#[read_component(Energy)]
+pub fn end_turn(
+    ecs: &SubWorld,
+    #[resource] turn_state: &mut TurnState,
+    #[resource] map: &Map,
+    #[resource] wave_manager: &WaveManager,
+    #[resource] level: &DungeonLevel
+) {
+    let mut new_state = match turn_state {
         TurnState::PlayerTurn => TurnState::MonsterTurn,
-        TurnState::MonsterTurn => TurnState::AwaitingInput
+        // Monster passes repeat until the player is the only one ready to act
+        TurnState::MonsterTurn => {
+            let mut actors = <(&Energy, Option<&Player>)>::query();
+            let (player_ready, others_ready) = actors
+                .iter(ecs)
+                .filter(|(energy, _)| energy.is_ready())
+                .fold((false, false), |(player_ready, others_ready), (_, player)| {
+                    (player_ready || player.is_some(), others_ready || player.is_none())
+                });
+            if player_ready && !others_ready {
+                TurnState::AwaitingInput
+            } else {
+                TurnState::MonsterTurn
+            }
+        }
+        _ => return
     };
 
+    let mut player = <(&Point, &Health, &Experience)>::query().filter(component::<Player>());
+    if let Some((pos, health, experience)) = player.iter(ecs).nth(0) {
+        // Level-ups are spent in the lull between waves
+        if new_state == TurnState::AwaitingInput && !wave_manager.wave_active && experience.unspent > 0 {
+            new_state = TurnState::ChoosingUpgrade;
+        }
+        // The stairs only open once every wave on this floor is beaten
+        if wave_manager.floor_cleared() {
+            if level.0 >= FINAL_LEVEL {
+                new_state = TurnState::Victory;
+            } else if map.tiles[map.point2d_to_index(*pos)] == TileType::DownStairs {
+                new_state = TurnState::NextLevel;
+            }
+        }
+        if health.current < 1 {
+            new_state = TurnState::GameOver;
+        }
+    }
+
     *turn_state = new_state;
 }
@@ -0,0 +1,74 @@
+use crate::prelude::*;
+
+// First in both the player and monster schedules. After the player moves it pays for that turn;
+// otherwise it winds the clock on until somebody can act, says how far with TimePassed, then
+// hands MyTurn to every monster and ally that can
+#[system]
+#[read_component(Energy)]
+#[read_component(Player)]
+#[read_component(Slowed)]
+#[read_component(Hasted)]
+#[read_component(MyTurn)]
+#[read_component(TimePassed)]
+pub fn energy(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] turn_state: &TurnState
+) {
+    let mut query = <(Entity, &Energy, Option<&Player>, Option<&Slowed>, Option<&Hasted>)>::query();
+
+    if *turn_state == TurnState::PlayerTurn {
+        query
+            .iter(ecs)
+            .filter(|(_, _, player, ..)| player.is_some())
+            .for_each(|(entity, energy, ..)| {
+                commands.add_component(*entity, Energy { current: energy.current - Energy::TURN, ..*energy });
+            });
+        return;
+    }
+
+    // Last pass's actors are done
+    <Entity>::query()
+        .filter(component::<MyTurn>())
+        .iter(ecs)
+        .for_each(|entity| commands.remove_component::<MyTurn>(*entity));
+    <Entity>::query()
+        .filter(component::<TimePassed>())
+        .iter(ecs)
+        .for_each(|entity| commands.remove(*entity));
+
+    let mut actors: Vec<(Entity, Energy, bool, i32)> = query
+        .iter(ecs)
+        .map(|(entity, energy, player, slowed, hasted)| {
+            let mut speed = energy.speed;
+            if slowed.is_some() {
+                speed /= 2;
+            }
+            if hasted.is_some() {
+                speed *= 2;
+            }
+            (*entity, *energy, player.is_some(), speed.max(1))
+        })
+        .collect();
+    if actors.is_empty() {
+        return;
+    }
+
+    let mut ticks = 0;
+    while !actors.iter().any(|(_, energy, ..)| energy.is_ready()) {
+        actors.iter_mut().for_each(|(_, energy, _, speed)| energy.current += *speed);
+        ticks += 1;
+    }
+    if ticks > 0 {
+        commands.push(((), TimePassed { ticks }));
+    }
+
+    for (entity, mut energy, is_player, _) in actors {
+        // The player waits for input and pays when it acts
+        if !is_player && energy.is_ready() {
+            energy.current -= Energy::TURN;
+            commands.add_component(entity, MyTurn);
+        }
+        commands.add_component(entity, energy);
+    }
+}
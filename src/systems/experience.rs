@@ -0,0 +1,37 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(GainExperience)]
+#[read_component(Experience)]
+pub fn experience(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] game_log: &mut GameLog
+) {
+    // Several kills can land in one turn, so total them up before touching the recipient
+    let mut gains: Vec<(Entity, i32)> = Vec::new();
+    <(Entity, &GainExperience)>::query().iter(ecs).for_each(|(message, gain)| {
+        match gains.iter_mut().find(|(entity, _)| *entity == gain.entity) {
+            Some((_, amount)) => *amount += gain.amount,
+            None => gains.push((gain.entity, gain.amount)),
+        }
+        commands.remove(*message);
+    });
+
+    for (entity, amount) in gains {
+        if let Some(experience) = ecs.entry_ref(entity).ok().and_then(|entry| entry.get_component::<Experience>().ok().copied()) {
+            let mut experience = Experience {
+                current: experience.current + amount,
+                ..experience
+            };
+            while experience.current >= experience.next_level {
+                experience.current -= experience.next_level;
+                experience.level += 1;
+                experience.next_level = Experience::needed_for(experience.level + 1);
+                experience.unspent += 1;
+                game_log.add(LogCategory::Level, format!("Player reaches level {}!", experience.level));
+            }
+            commands.add_component(entity, experience);
+        }
+    }
+}
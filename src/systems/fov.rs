@@ -0,0 +1,26 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Player)]
+#[write_component(FieldOfView)]
+pub fn fov(ecs: &mut SubWorld, #[resource] map: &mut Map) {
+    let mut views = <(&Point, &mut FieldOfView, Option<&Player>)>::query();
+    views
+        .iter_mut(ecs)
+        .filter(|(_, fov, _)| fov.is_dirty)
+        .for_each(|(pos, fov, player)| {
+            fov.visible_tiles = FieldOfViewAlg::SymmetricShadowcasting
+                .field_of_view_set(*pos, fov.radius, map);
+            fov.is_dirty = false;
+
+            // Only the player remembers what it has seen
+            if player.is_some() {
+                fov.visible_tiles.iter().for_each(|tile| {
+                    if let Some(idx) = map.try_idx(*tile) {
+                        map.revealed_tiles[idx] = true;
+                    }
+                });
+            }
+        });
+}
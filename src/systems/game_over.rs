@@ -0,0 +1,23 @@
+use crate::prelude::*;
+
+#[system]
+pub fn game_over(
+    #[resource] level: &DungeonLevel,
+    #[resource] wave_manager: &WaveManager,
+    #[resource] seed: &Seed
+) {
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.target(2);
+
+    draw_batch.print_color_centered(30, "Your quest has ended.", ColorPair::new(RED, BLACK));
+    draw_batch.print_color_centered(
+        33,
+        format!("You fell on floor {} during wave {}.", level.0, wave_manager.current_wave),
+        ColorPair::new(WHITE, BLACK)
+    );
+    draw_batch.print_color_centered(34, format!("Seed: {}", seed.0), ColorPair::new(GRAY, BLACK));
+    draw_batch.print_color_centered(38, "Press 1 to play again with a new seed.", ColorPair::new(GREEN, BLACK));
+    draw_batch.print_color_centered(39, "Press 2 to retry the same seed.", ColorPair::new(GREEN, BLACK));
+
+    draw_batch.submit(12000).expect("Batch error");
+}
@@ -3,24 +3,58 @@ use crate::prelude::*;
 #[system]
 #[read_component(Health)]
 #[read_component(Mana)]
-#[read_component(CanDash)]
-#[read_component(CanCastFireball)]
+#[read_component(Abilities)]
 #[read_component(Player)]
+#[read_component(Burning)]
+#[read_component(Poisoned)]
+#[read_component(Stunned)]
+#[read_component(Slowed)]
+#[read_component(Hasted)]
+#[read_component(Regenerating)]
+#[read_component(Point)]
+#[read_component(Item)]
+#[read_component(Name)]
+#[read_component(Equippable)]
+#[read_component(Equipped)]
+#[read_component(EquipmentBonus)]
+#[read_component(Experience)]
 pub fn hud(
     ecs: &SubWorld,
     #[resource] targeting_state: &TargetingState,
-    #[resource] wave_manager: &WaveManager
+    #[resource] wave_manager: &WaveManager,
+    #[resource] level: &DungeonLevel,
+    #[resource] seed: &Seed,
+    #[resource] bindings: &Bindings,
+    #[resource] map: &Map
 ) {
-    let mut player_query = <(&Health, &Mana, &CanDash, &CanCastFireball)>::query()
+    let mut player_query = <(Entity, &Health, &Mana, &Abilities, &Experience)>::query()
         .filter(component::<Player>());
     
-    if let Some((player_health, player_mana, dash_ability, fireball_ability)) = 
+    if let Some((player_entity, player_health, player_mana, abilities, experience)) = 
         player_query.iter(ecs).nth(0) {
         
         let mut draw_batch = DrawBatch::new();
         draw_batch.target(2);
+        let moves = bindings.describe_all(&Action::ORTHOGONAL);
+        let wait = bindings.describe(Action::Wait);
         
-        if wave_manager.current_wave <= 3 {
+        draw_batch.print_color_right(
+            Point::new(SCREEN_WIDTH * 2 - 1, 1),
+            format!("Seed: {}", seed.0),
+            ColorPair::new(GRAY, BLACK)
+        );
+        draw_batch.print_color(
+            Point::new(1, 1),
+            format!("Floor {} of {}", level.0, FINAL_LEVEL),
+            ColorPair::new(WHITE, BLACK)
+        );
+        draw_batch.print_color(
+            Point::new(1, 2),
+            format!("Level {}  XP {}/{}", experience.level, experience.current, experience.next_level),
+            ColorPair::new(GREEN, BLACK)
+        );
+        
+        if wave_manager.current_wave <= WAVES_PER_LEVEL {
             if wave_manager.wave_active {
                 draw_batch.print_color_centered(1, 
                     format!("Wave {} - Enemies: {}", wave_manager.current_wave, wave_manager.enemies_remaining),
@@ -34,34 +68,33 @@ pub fn hud(
             }
         } else {
             draw_batch.print_color_centered(1, 
-                "All waves completed! Victory!",
+                "Floor cleared! Find the stairs (>) to descend",
                 ColorPair::new(GREEN, BLACK)
             );
         }
         
         match targeting_state {
             TargetingState::None => {
-                if wave_manager.current_wave <= 3 {
-                    draw_batch.print_centered(2, "Survive the waves! Cursor keys to move, SPACE to wait.");
+                if wave_manager.current_wave <= WAVES_PER_LEVEL {
+                    draw_batch.print_centered(2, format!("Survive the waves! {} to move, {} to wait.", moves, wait));
                 }
             }
-            TargetingState::SelectingDashTarget => {
-                draw_batch.print_color_centered(2, 
-                    "DASH MODE: Click to dash to target position",
-                    ColorPair::new(CYAN, BLACK)
-                );
-                draw_batch.print_color_centered(3, 
-                    "ESC or Right-click to cancel",
-                    ColorPair::new(WHITE, BLACK)
-                );
-            }
-            TargetingState::SelectingFireballTarget => {
-                draw_batch.print_color_centered(2, 
-                    "FIREBALL MODE: Click to cast at target",
-                    ColorPair::new(YELLOW, BLACK)
-                );
+            TargetingState::Selecting { ability: selected, .. } => {
+                if let Some(ability) = abilities.0.get(*selected) {
+                    let how = if ability.is_self_cast() {
+                        format!("Click, {} or {} again to cast",
+                            bindings.describe(Action::Confirm), bindings.describe(Action::CastAbility(*selected)))
+                    } else {
+                        format!("Click or {} to cast, {} or {} to aim",
+                            bindings.describe(Action::Confirm), moves, bindings.describe(Action::NextTarget))
+                    };
+                    draw_batch.print_color_centered(2, 
+                        format!("{} MODE: {}", ability.name.to_uppercase(), how),
+                        ColorPair::new(ability.color, BLACK)
+                    );
+                }
                 draw_batch.print_color_centered(3, 
-                    "ESC or Right-click to cancel",
+                    format!("{} or Right-click to cancel", bindings.describe(Action::Cancel)),
                     ColorPair::new(WHITE, BLACK)
                 );
             }
@@ -97,61 +130,108 @@ pub fn hud(
             ColorPair::new(WHITE, BLUE)
         );
         
+        if let Ok(player_entry) = ecs.entry_ref(*player_entity) {
+            let mut effect_x = 1;
+            for (effect, turns) in StatusEffect::active_on(&player_entry) {
+                let label = format!("{} ({})", effect.name(), turns);
+                draw_batch.print_color(
+                    Point::new(effect_x, mana_y + 1),
+                    &label,
+                    ColorPair::new(effect.color(), BLACK)
+                );
+                effect_x += label.len() as i32 + 2;
+            }
+        }
+        
         if !targeting_state.is_targeting() {
             draw_batch.print_centered(skills_y, "Skills:");
             
-            let dash_color = if player_mana.current >= dash_ability.cost {
-                ColorPair::new(WHITE, BLACK)
-            } else {
-                ColorPair::new(GRAY, BLACK)
-            };
-            draw_batch.print_color(
-                Point::new(5, skills_y + 1),
-                format!("(D) Dash - Cost: {} mana", dash_ability.cost),
-                dash_color
-            );
-            
-            let fireball_color = if player_mana.current >= fireball_ability.cost {
-                ColorPair::new(WHITE, BLACK)
-            } else {
-                ColorPair::new(GRAY, BLACK)
-            };
-            draw_batch.print_color(
-                Point::new(5, skills_y + 2),
-                format!("(F) Fireball - Cost: {} mana", fireball_ability.cost),
-                fireball_color
-            );
-            
-            draw_batch.print_centered(skills_y + 4, "Press D or F to select skills");
-            draw_batch.print_centered(skills_y + 5, "Move with arrow keys, SPACE to wait, attack by bumping onto enemies");
-        } else {
-            match targeting_state {
-                TargetingState::SelectingDashTarget => {
-                    draw_batch.print_color_centered(skills_y, 
-                        format!("DASH: Range {} tiles, Cost {} mana", dash_ability.range, dash_ability.cost),
-                        ColorPair::new(CYAN, BLACK)
-                    );
-                    draw_batch.print_centered(skills_y + 1, "Cyan dots show valid dash destinations");
-                    draw_batch.print_centered(skills_y + 2, "Click exactly where you want to dash");
-                    draw_batch.print_color_centered(skills_y + 3, 
-                        "Cursor: Green=valid | Red=invalid | Blue M=no mana",
-                        ColorPair::new(WHITE, BLACK)
-                    );
+            for (i, ability) in abilities.0.iter().enumerate() {
+                let color = if player_mana.current >= ability.cost && ability.is_ready() {
+                    ColorPair::new(WHITE, BLACK)
+                } else {
+                    ColorPair::new(GRAY, BLACK)
+                };
+                let mut line = format!("({}) {} - Cost: {} mana",
+                    bindings.describe(Action::CastAbility(i)), ability.name, ability.cost);
+                let status = ability.cooldown_status();
+                if !status.is_empty() {
+                    line = format!("{} [{}]", line, status);
                 }
-                TargetingState::SelectingFireballTarget => {
-                    draw_batch.print_color_centered(skills_y, 
-                        format!("FIREBALL: Range {} tiles, Damage {}, Cost {} mana", 
-                            fireball_ability.range, fireball_ability.damage, fireball_ability.cost),
+                draw_batch.print_color(Point::new(5, skills_y + 1 + i as i32), line, color);
+            }
+            
+            // Worn gear sits opposite the skills, with a comparison for anything underfoot
+            let gear_x = SCREEN_WIDTH * 2 - 5;
+            draw_batch.print_right(Point::new(gear_x, skills_y), "Equipment:");
+            for (i, slot) in [EquipmentSlot::Weapon, EquipmentSlot::Armor, EquipmentSlot::Trinket].iter().enumerate() {
+                let worn = <(&Equipped, &Name)>::query()
+                    .iter(ecs)
+                    .find(|(equipped, _)| equipped.owner == *player_entity && equipped.slot == *slot)
+                    .map_or_else(|| "-".to_string(), |(_, name)| name.0.clone());
+                draw_batch.print_right(
+                    Point::new(gear_x, skills_y + 1 + i as i32),
+                    format!("{}: {}", slot.name(), worn)
+                );
+            }
+            let underfoot = ecs.entry_ref(*player_entity).ok()
+                .and_then(|entry| entry.get_component::<Point>().ok().copied())
+                .and_then(|player_pos| <(Entity, &Point, &Name)>::query()
+                    .filter(component::<Item>())
+                    .iter(ecs)
+                    .find(|(_, pos, _)| **pos == player_pos)
+                    .map(|(item, _, name)| (*item, name.0.clone()))
+                );
+            if let Some((item, name)) = underfoot {
+                if let Some((_, delta)) = EquipmentBonus::compare(ecs, *player_entity, item) {
+                    draw_batch.print_color_right(
+                        Point::new(gear_x, skills_y + 4),
+                        format!("Here: {} ({})", name, delta.describe()),
                         ColorPair::new(YELLOW, BLACK)
                     );
-                    draw_batch.print_centered(skills_y + 1, "Yellow dots: valid targets | Orange: blast area");
-                    draw_batch.print_centered(skills_y + 2, "Green line shows line of sight to cursor");
-                    draw_batch.print_color_centered(skills_y + 3, 
-                        "Cursor: Green=valid | Red=invalid | Blue M=no mana",
-                        ColorPair::new(WHITE, BLACK)
-                    );
                 }
-                _ => {}
+            }
+
+            // Help goes below whichever is longer, the skills list or the equipment column
+            let help_y = skills_y + 2 + (abilities.0.len() as i32).max(3);
+            let keys: Vec<String> = (0..abilities.0.len())
+                .map(|i| bindings.describe(Action::CastAbility(i)))
+                .collect();
+            draw_batch.print_centered(help_y, format!("Press {} to select skills", keys.join(", ")));
+            draw_batch.print_centered(help_y + 1,
+                format!("Move with {}, {} to wait, attack by bumping onto enemies", moves, wait));
+            let mut next_help = help_y + 2;
+            if map.topology == Topology::EightWay {
+                draw_batch.print_centered(next_help, format!("Diagonals: {}, or hold two arrows together",
+                    bindings.describe_all(&Action::DIAGONAL)));
+                next_help += 1;
+            }
+            draw_batch.print_centered(next_help, format!("{} to pick up items, {} for inventory, {} for the message log",
+                bindings.describe(Action::PickUp), bindings.describe(Action::Inventory), bindings.describe(Action::Log)));
+            draw_batch.print_centered(next_help + 1, format!("Click a tile to walk there, {} to explore",
+                bindings.describe(Action::Explore)));
+        } else {
+            if let Some(ability) = targeting_state.ability().and_then(|selected| abilities.0.get(selected)) {
+                draw_batch.print_color_centered(skills_y, 
+                    format!("{}: {}", ability.name.to_uppercase(), ability.summary()),
+                    ColorPair::new(ability.color, BLACK)
+                );
+                draw_batch.print_color_centered(skills_y + 1, &ability.description, ColorPair::new(GRAY, BLACK));
+                let help = match (&ability.targeting, &ability.effect) {
+                    (Targeting::Path, AbilityEffect::Summon { .. }) => ["Dots show where it can appear", "Click an empty tile to summon"],
+                    (Targeting::Path, _) => ["Dots show where you can land", "Click exactly where you want to go"],
+                    (Targeting::Blast { .. }, _) => ["Dots: valid targets | Orange: blast area", "Green line shows line of sight to cursor"],
+                    (Targeting::Chain { .. }, _) => ["Rings: enemies in reach | Orange: every enemy struck", "The bolt arcs on to the nearest enemy each time"],
+                    (Targeting::Caster { .. }, AbilityEffect::Blink) => ["Question marks show where you might land", "The destination is chosen at random"],
+                    (Targeting::Caster { radius: 0 }, _) => ["Only you are affected", ""],
+                    (Targeting::Caster { .. }, _) => ["Stars show the area around you", "Every enemy inside is caught"],
+                };
+                draw_batch.print_centered(skills_y + 2, help[0]);
+                draw_batch.print_centered(skills_y + 3, help[1]);
+                draw_batch.print_color_centered(skills_y + 4, 
+                    "Cursor: Green=valid | Red=invalid | Blue M=no mana",
+                    ColorPair::new(WHITE, BLACK)
+                );
             }
         }
         
@@ -0,0 +1,106 @@
+use crate::prelude::*;
+
+const FIRST_LINE: i32 = 16;
+
+#[system]
+#[read_component(Item)]
+#[read_component(Carried)]
+#[read_component(Name)]
+#[read_component(Player)]
+#[read_component(Equippable)]
+#[read_component(Equipped)]
+#[read_component(EquipmentBonus)]
+pub fn inventory(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] key: &Option<VirtualKeyCode>,
+    #[resource] bindings: &Bindings,
+    #[resource] turn_state: &mut TurnState
+) {
+    let player = match <Entity>::query().filter(component::<Player>()).iter(ecs).nth(0) {
+        Some(player) => *player,
+        None => return,
+    };
+    let items: Vec<(Entity, String)> = <(Entity, &Carried, &Name)>::query()
+        .filter(component::<Item>())
+        .iter(ecs)
+        .filter(|(_, carried, _)| carried.0 == player)
+        .map(|(entity, _, name)| (*entity, name.0.clone()))
+        .collect();
+    let equipped: Vec<(EquipmentSlot, String, EquipmentBonus)> = <(&Equipped, &Name, &EquipmentBonus)>::query()
+        .iter(ecs)
+        .filter(|(equipped, _, _)| equipped.owner == player)
+        .map(|(equipped, name, bonus)| (equipped.slot, name.0.clone(), *bonus))
+        .collect();
+
+    if let Some(key) = *key {
+        match bindings.action(key) {
+            Some(Action::Cancel) | Some(Action::Inventory) => {
+                *turn_state = TurnState::AwaitingInput;
+                return;
+            }
+            _ => {
+                // Using an item takes the player's turn
+                if let Some((item, _)) = inventory_slot(key).and_then(|slot| items.get(slot)) {
+                    commands.push(((), ActivateItem {
+                        used_by: player,
+                        item: *item,
+                    }));
+                    *turn_state = TurnState::PlayerTurn;
+                    return;
+                }
+            }
+        }
+    }
+
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.target(2);
+    draw_batch.print_color_centered(FIRST_LINE - 3, "Inventory", ColorPair::new(YELLOW, BLACK));
+    if items.is_empty() {
+        draw_batch.print_color_centered(FIRST_LINE, "You are carrying nothing.", ColorPair::new(GRAY, BLACK));
+    }
+    items.iter().enumerate().for_each(|(i, (item, name))| {
+        // Gear is listed with what equipping it would change
+        let line = match EquipmentBonus::compare(ecs, player, *item) {
+            Some((slot, delta)) => format!("({}) {} [{}] {}", i + 1, name, slot.name(), delta.describe()),
+            None => format!("({}) {}", i + 1, name),
+        };
+        draw_batch.print_color_centered(FIRST_LINE + i as i32, line, ColorPair::new(WHITE, BLACK));
+    });
+
+    let equipped_y = FIRST_LINE + MAX_CARRIED as i32 + 2;
+    draw_batch.print_color_centered(equipped_y, "Equipped", ColorPair::new(YELLOW, BLACK));
+    [EquipmentSlot::Weapon, EquipmentSlot::Armor, EquipmentSlot::Trinket]
+        .iter()
+        .enumerate()
+        .for_each(|(i, slot)| {
+            let line = match equipped.iter().find(|(worn_slot, _, _)| worn_slot == slot) {
+                Some((_, name, bonus)) => format!("{}: {} ({})", slot.name(), name, bonus.describe()),
+                None => format!("{}: nothing", slot.name()),
+            };
+            draw_batch.print_color_centered(equipped_y + 1 + i as i32, line, ColorPair::new(WHITE, BLACK));
+        });
+
+    draw_batch.print_color_centered(
+        equipped_y + 6,
+        format!("Press a number to use or equip an item, {} or {} to close",
+            bindings.describe(Action::Inventory), bindings.describe(Action::Cancel)),
+        ColorPair::new(GRAY, BLACK)
+    );
+    draw_batch.submit(12000).expect("Batch error");
+}
+
+pub(super) fn inventory_slot(key: VirtualKeyCode) -> Option<usize> {
+    match key {
+        VirtualKeyCode::Key1 => Some(0),
+        VirtualKeyCode::Key2 => Some(1),
+        VirtualKeyCode::Key3 => Some(2),
+        VirtualKeyCode::Key4 => Some(3),
+        VirtualKeyCode::Key5 => Some(4),
+        VirtualKeyCode::Key6 => Some(5),
+        VirtualKeyCode::Key7 => Some(6),
+        VirtualKeyCode::Key8 => Some(7),
+        VirtualKeyCode::Key9 => Some(8),
+        _ => None,
+    }
+}
@@ -0,0 +1,103 @@
+use crate::prelude::*;
+use super::inventory::inventory_slot;
+
+const FIRST_LINE: i32 = 20;
+
+#[system]
+#[read_component(Player)]
+#[read_component(Experience)]
+#[read_component(Health)]
+#[read_component(Mana)]
+#[read_component(CombatStats)]
+#[read_component(Abilities)]
+pub fn level_up(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] key: &Option<VirtualKeyCode>,
+    #[resource] turn_state: &mut TurnState,
+    #[resource] choices: &mut UpgradeChoices,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] game_log: &mut GameLog
+) {
+    let mut players = <(Entity, &Experience, &Health, &Mana, &CombatStats, &Abilities)>::query()
+        .filter(component::<Player>());
+    let (player, experience, health, mana, stats, abilities) = match players.iter(ecs).nth(0) {
+        Some(player) => player,
+        None => return,
+    };
+
+    if choices.0.is_empty() {
+        choices.0 = Upgrade::roll(rng, abilities);
+    }
+
+    if let Some(upgrade) = key.and_then(inventory_slot).and_then(|slot| choices.0.get(slot)).copied() {
+        match upgrade {
+            Upgrade::MaxHealth => commands.add_component(*player, Health {
+                current: health.current + 5,
+                max: health.max + 5,
+            }),
+            Upgrade::MaxMana => commands.add_component(*player, Mana {
+                current: mana.current + 3,
+                max: mana.max + 3,
+            }),
+            Upgrade::Power => commands.add_component(*player, CombatStats { power: stats.power + 1, ..*stats }),
+            Upgrade::Defense => commands.add_component(*player, CombatStats { defense: stats.defense + 1, ..*stats }),
+            Upgrade::BlastRadius => commands.add_component(*player, abilities.with(
+                Ability::is_blast,
+                |ability| if let Targeting::Blast { radius } = &mut ability.targeting { *radius += 1; }
+            )),
+            Upgrade::SpellDamage => commands.add_component(*player, abilities.with(
+                Ability::deals_damage,
+                |ability| ability.add_damage(2)
+            )),
+            Upgrade::CheaperDash => commands.add_component(*player, abilities.with(
+                Ability::is_movement,
+                |ability| ability.cost = (ability.cost - 1).max(1)
+            )),
+            Upgrade::DashRange => commands.add_component(*player, abilities.with(
+                Ability::is_movement,
+                |ability| ability.range += 1
+            )),
+        }
+        commands.add_component(*player, Experience {
+            unspent: experience.unspent - 1,
+            ..*experience
+        });
+        game_log.add(LogCategory::Level, format!("Player learns {}", upgrade.description()));
+
+        // Several level-ups at once get a fresh pick each
+        choices.0.clear();
+        if experience.unspent <= 1 {
+            *turn_state = TurnState::AwaitingInput;
+        }
+        return;
+    }
+
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.target(2);
+    draw_batch.print_color_centered(
+        FIRST_LINE - 4,
+        format!("Level {} - choose an upgrade", experience.level),
+        ColorPair::new(YELLOW, BLACK)
+    );
+    if experience.unspent > 1 {
+        draw_batch.print_color_centered(
+            FIRST_LINE - 3,
+            format!("({} upgrades to choose)", experience.unspent),
+            ColorPair::new(GRAY, BLACK)
+        );
+    }
+    choices.0.iter().enumerate().for_each(|(i, upgrade)| {
+        draw_batch.print_color_centered(
+            FIRST_LINE + i as i32 * 2,
+            format!("({}) {}", i + 1, upgrade.description()),
+            ColorPair::new(WHITE, BLACK)
+        );
+    });
+    draw_batch.print_color_centered(
+        FIRST_LINE + UPGRADE_CHOICES as i32 * 2 + 2,
+        "Press a number to choose",
+        ColorPair::new(GRAY, BLACK)
+    );
+    draw_batch.submit(12000).expect("Batch error");
+}
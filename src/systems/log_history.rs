@@ -0,0 +1,59 @@
+use crate::prelude::*;
+
+const FIRST_LINE: i32 = 4;
+const VISIBLE_LINES: usize = (SCREEN_HEIGHT * 2 - 8) as usize;
+
+#[system]
+pub fn log_history(
+    #[resource] key: &Option<VirtualKeyCode>,
+    #[resource] bindings: &Bindings,
+    #[resource] game_log: &mut GameLog,
+    #[resource] turn_state: &mut TurnState
+) {
+    let max_offset = game_log.entries().len().saturating_sub(VISIBLE_LINES);
+    if let Some(key) = *key {
+        match (key, bindings.action(key)) {
+            (_, Some(Action::MoveNorth)) => game_log.history_offset += 1,
+            (_, Some(Action::MoveSouth)) => game_log.history_offset = game_log.history_offset.saturating_sub(1),
+            (VirtualKeyCode::PageUp, _) => game_log.history_offset += VISIBLE_LINES,
+            (VirtualKeyCode::PageDown, _) => {
+                game_log.history_offset = game_log.history_offset.saturating_sub(VISIBLE_LINES);
+            }
+            (_, Some(Action::Cancel)) | (_, Some(Action::Log)) => {
+                game_log.history_offset = 0;
+                *turn_state = TurnState::AwaitingInput;
+                return;
+            }
+            _ => {}
+        }
+    }
+    game_log.history_offset = game_log.history_offset.min(max_offset);
+
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.target(2);
+    draw_batch.print_color_centered(1, "Message History", ColorPair::new(YELLOW, BLACK));
+    draw_batch.print_color_centered(
+        2,
+        format!("{}/{} or PgUp/PgDn to scroll, {} or {} to close",
+            bindings.describe(Action::MoveNorth), bindings.describe(Action::MoveSouth),
+            bindings.describe(Action::Log), bindings.describe(Action::Cancel)),
+        ColorPair::new(GRAY, BLACK)
+    );
+
+    // Newest entries sit at the bottom; scrolling moves the window back in time
+    let entries = game_log.entries();
+    let end = entries.len() - game_log.history_offset;
+    let start = end.saturating_sub(VISIBLE_LINES);
+    entries[start..end]
+        .iter()
+        .enumerate()
+        .for_each(|(i, entry)| {
+            draw_batch.print_color(
+                Point::new(2, FIRST_LINE + i as i32),
+                &entry.text,
+                ColorPair::new(entry.category.color(), BLACK)
+            );
+        });
+
+    draw_batch.submit(12000).expect("Batch error");
+}
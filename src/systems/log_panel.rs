@@ -0,0 +1,32 @@
+use crate::prelude::*;
+
+const PANEL_LINES: usize = 6;
+
+#[system]
+pub fn log_panel(#[resource] game_log: &GameLog, #[resource] bindings: &Bindings) {
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.target(2);
+
+    let bottom = SCREEN_HEIGHT * 2 - 1;
+    let top = bottom - PANEL_LINES as i32;
+    draw_batch.print_color(
+        Point::new(1, top),
+        format!("Log ({} for full history)", bindings.describe(Action::Log)),
+        ColorPair::new(GRAY, BLACK)
+    );
+
+    let entries = game_log.entries();
+    let first = entries.len().saturating_sub(PANEL_LINES);
+    entries[first..]
+        .iter()
+        .enumerate()
+        .for_each(|(i, entry)| {
+            draw_batch.print_color(
+                Point::new(1, top + 1 + i as i32),
+                &entry.text,
+                ColorPair::new(entry.category.color(), BLACK)
+            );
+        });
+
+    draw_batch.submit(10200).expect("Batch error");
+}
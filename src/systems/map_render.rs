@@ -1,7 +1,19 @@
 use crate::prelude::*;
 
 #[system]
-pub fn map_render(#[resource] map: &Map, #[resource] camera: &Camera) {
+#[read_component(FieldOfView)]
+#[read_component(Player)]
+pub fn map_render(
+    ecs: &SubWorld,
+    #[resource] map: &Map,
+    #[resource] camera: &Camera
+) {
+    let mut fov = <&FieldOfView>::query().filter(component::<Player>());
+    let player_fov = match fov.iter(ecs).nth(0) {
+        Some(player_fov) => player_fov,
+        None => return,
+    };
+
     let mut draw_batch = DrawBatch::new();
     draw_batch.target(0);
     for y in camera.top_y ..= camera.bottom_y {
@@ -9,19 +21,25 @@ pub fn map_render(#[resource] map: &Map, #[resource] camera: &Camera) {
             let pt = Point::new(x, y);
             let offset = Point::new(camera.left_x, camera.top_y);
             if map.in_bounds(pt) {
-                let idx = map_idx(x, y);
-                let glyph = match map.tiles[idx] {
-                    TileType::Floor => to_cp437('.'),
-                    TileType::Wall => to_cp437('#'),
-                };
-                draw_batch.set(
-                    pt - offset,
-                    ColorPair::new(
-                        WHITE,
-                        BLACK
-                    ),
-                    glyph
-                );
+                let idx = map.map_idx(x, y);
+                let is_visible = player_fov.visible_tiles.contains(&pt);
+                if is_visible || map.revealed_tiles[idx] {
+                    let glyph = match map.tiles[idx] {
+                        TileType::Floor => to_cp437('.'),
+                        TileType::Wall => to_cp437('#'),
+                        TileType::DownStairs => to_cp437('>'),
+                    };
+                    // Remembered tiles are drawn dimmed
+                    let tint = if is_visible { WHITE } else { DARK_GRAY };
+                    draw_batch.set(
+                        pt - offset,
+                        ColorPair::new(
+                            tint,
+                            BLACK
+                        ),
+                        glyph
+                    );
+                }
             }
         }
     }
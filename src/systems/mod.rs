@@ -3,25 +3,47 @@ use crate::prelude::*;
 mod map_render;
 mod entity_render;
 mod player_input;
+mod targeting_input;
+mod travel_input;
+mod travel;
 mod collisions;
 mod pathfinding;
 mod end_turn;
 mod movement;
 mod hud;
 mod tooltips;
-mod dash;
-mod fireball;
-mod fireball_effects;
+mod cast_ability;
+mod ability_cooldowns;
+mod spell_effects;
 mod mana_regeneration;
 mod targeting_highlights;
 mod targeting_cursor;
 mod debug_coordinates;
 mod wave_management;
 mod combat;
+mod fov;
+mod game_over;
+mod victory;
+mod status_effects;
+mod log_panel;
+mod log_history;
+mod use_items;
+mod inventory;
+mod experience;
+mod level_up;
+mod allies;
+mod energy;
 
 pub fn build_input_scheduler() -> Schedule {
     Schedule::builder()
-        .add_system(player_input::player_input_system())
+        // Aiming goes first so a key that fires a spell can't also start a new one
+        .add_system(targeting_input::targeting_input_system())
+        .add_system(travel_input::travel_input_system(false))
+        .add_system(player_input::player_input_system(None))
+        // Walking goes after the flush so it sees any walk started, redirected or stopped this frame
+        .flush()
+        .add_system(travel::travel_system())
+        .add_system(fov::fov_system())
         .flush()
         .add_system(map_render::map_render_system())
         .add_system(entity_render::entity_render_system())
@@ -29,9 +51,10 @@ pub fn build_input_scheduler() -> Schedule {
         .add_system(targeting_highlights::targeting_highlights_system())
         .flush()
         .add_system(targeting_cursor::targeting_cursor_system())
-        .add_system(fireball_effects::fireball_effects_system())
+        .add_system(spell_effects::spell_effects_system())
         .flush()
         .add_system(hud::hud_system())
+        .add_system(log_panel::log_panel_system())
         .add_system(tooltips::tooltips_system())
         .add_system(debug_coordinates::debug_coordinates_system())
         .build()
@@ -39,17 +62,22 @@ pub fn build_input_scheduler() -> Schedule {
 
 pub fn build_player_scheduler() -> Schedule {
     Schedule::builder()
-        .add_system(dash::dash_system())
-        .add_system(dash::dash_to_point_system())
-        .add_system(fireball::fireball_system())
+        .add_system(energy::energy_system())
+        .flush()
+        .add_system(use_items::use_items_system())
+        .flush()
+        .add_system(cast_ability::cast_ability_system())
         .flush()
         .add_system(movement::movement_system())
         .flush()
+        .add_system(fov::fov_system())
+        .flush()
         .add_system(combat::combat_system())
         .flush()
+        .add_system(experience::experience_system())
         .add_system(collisions::collisions_system())
         .flush()
-        .add_system(mana_regeneration::mana_regeneration_system())
+        .add_system(status_effects::status_effects_system())
         .flush()
         .add_system(wave_management::wave_management_system())
         .flush()
@@ -59,9 +87,10 @@ pub fn build_player_scheduler() -> Schedule {
         .add_system(targeting_highlights::targeting_highlights_system())
         .flush()
         .add_system(targeting_cursor::targeting_cursor_system())
-        .add_system(fireball_effects::fireball_effects_system())
+        .add_system(spell_effects::spell_effects_system())
         .flush()
         .add_system(hud::hud_system())
+        .add_system(log_panel::log_panel_system())
         .add_system(debug_coordinates::debug_coordinates_system())
         .add_system(end_turn::end_turn_system())
         .build()
@@ -69,18 +98,72 @@ pub fn build_player_scheduler() -> Schedule {
 
 pub fn build_monster_scheduler() -> Schedule {
     Schedule::builder()
+        // Only monsters and allies with enough energy act in each pass
+        .add_system(energy::energy_system())
+        .flush()
+        // Recharging goes by however long the clock ran before this pass
+        .add_system(ability_cooldowns::ability_cooldowns_system())
+        .add_system(mana_regeneration::mana_regeneration_system())
         .add_system(pathfinding::pathfinding_system())
+        .add_system(allies::allies_system())
         .flush()
         .add_system(movement::movement_system())
         .flush()
+        .add_system(fov::fov_system())
+        .flush()
         .add_system(combat::combat_system())
         .flush()
         .add_system(collisions::collisions_system())
         .flush()
+        .add_system(status_effects::status_effects_system())
+        .flush()
         .add_system(map_render::map_render_system())
         .add_system(entity_render::entity_render_system())
         .flush()
         .add_system(hud::hud_system())
+        .add_system(log_panel::log_panel_system())
         .add_system(end_turn::end_turn_system())
         .build()
-}
\ No newline at end of file
+}
+
+pub fn build_game_over_scheduler() -> Schedule {
+    Schedule::builder()
+        .add_system(map_render::map_render_system())
+        .add_system(entity_render::entity_render_system())
+        .flush()
+        .add_system(game_over::game_over_system())
+        .build()
+}
+
+pub fn build_victory_scheduler() -> Schedule {
+    Schedule::builder()
+        .add_system(map_render::map_render_system())
+        .add_system(entity_render::entity_render_system())
+        .flush()
+        .add_system(victory::victory_system())
+        .build()
+}
+
+pub fn build_log_history_scheduler() -> Schedule {
+    Schedule::builder()
+        .add_system(log_history::log_history_system())
+        .build()
+}
+
+pub fn build_inventory_scheduler() -> Schedule {
+    Schedule::builder()
+        .add_system(map_render::map_render_system())
+        .add_system(entity_render::entity_render_system())
+        .flush()
+        .add_system(inventory::inventory_system())
+        .build()
+}
+
+pub fn build_level_up_scheduler() -> Schedule {
+    Schedule::builder()
+        .add_system(map_render::map_render_system())
+        .add_system(entity_render::entity_render_system())
+        .flush()
+        .add_system(level_up::level_up_system())
+        .build()
+}
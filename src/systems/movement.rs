@@ -2,6 +2,7 @@ use crate::prelude::*;
 
 #[system(for_each)]
 #[read_component(Player)]
+#[read_component(FieldOfView)]
 pub fn movement(
     entity: &Entity,
     want_move: &WantsToMove,
@@ -13,10 +14,11 @@ pub fn movement(
     if map.can_enter_tile(want_move.destination) {
         commands.add_component(want_move.entity, want_move.destination);
 
-        if ecs.entry_ref(want_move.entity)
-            .unwrap()
-            .get_component::<Player>().is_ok()
-        {
+        let entry = ecs.entry_ref(want_move.entity).unwrap();
+        if let Ok(fov) = entry.get_component::<FieldOfView>() {
+            commands.add_component(want_move.entity, fov.clone_dirty());
+        }
+        if entry.get_component::<Player>().is_ok() {
             camera.on_player_move(want_move.destination);
         }
     }
@@ -1,15 +1,37 @@
 use crate::prelude::*;
-use std::collections::VecDeque;
+
+// Ranged monsters back away once the player gets this close
+const TOO_CLOSE: f32 = 2.5;
+// With every tile beside the player taken, the rest hang back about this far
+const HOLD_BACK: i32 = 3;
+// A creature in the way is worth walking this much further to get around
+const CROWD_COST: f32 = 5.0;
+
+#[derive(Clone, Copy)]
+enum Intent {
+    Attack(Entity),
+    Step(Point),
+}
 
 #[system]
 #[read_component(Point)]
-#[read_component(FollowsPlayer)]
+#[read_component(Behaviour)]
+#[read_component(Awareness)]
+#[read_component(FleesBelow)]
+#[read_component(Health)]
+#[read_component(Name)]
 #[read_component(Player)]
 #[read_component(Enemy)]
+#[read_component(Stunned)]
+#[read_component(MyTurn)]
+#[read_component(Ally)]
 pub fn pathfinding(
     ecs: &SubWorld, 
     commands: &mut CommandBuffer,
-    #[resource] map: &Map
+    #[resource] map: &Map,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] distance_maps: &mut DistanceMaps,
+    #[resource] game_log: &mut GameLog
 ) {
     let (player_pos, player_entity) = {
         let mut player_query = <(Entity, &Point)>::query().filter(component::<Player>());
@@ -20,139 +42,257 @@ pub fn pathfinding(
         }
     };
     
-    let flow_field = create_flow_field(map, player_pos);
+    // Only rebuilt when the player has moved since last time
+    let (chase_map, flee_map) = distance_maps.around(map, player_pos);
     
-    let enemy_positions: Vec<Point> = <&Point>::query()
-        .filter(component::<Enemy>())
+    // Summoned allies block the way just like other enemies do
+    let mut enemy_positions: Vec<Point> = <&Point>::query()
+        .filter(component::<Enemy>() | component::<Ally>())
         .iter(ecs)
         .copied()
         .collect();
+    let allies: Vec<(Entity, Point)> = <(Entity, &Point)>::query()
+        .filter(component::<Ally>())
+        .iter(ecs)
+        .map(|(entity, pos)| (*entity, *pos))
+        .collect();
     
-    let mut enemy_query = <(Entity, &Point, &FollowsPlayer)>::query()
-        .filter(component::<Enemy>());
+    // Stunned enemies waste their turn entirely
+    let mut enemy_query = <(Entity, &Point, &Behaviour, &Awareness, &Health, &Name, Option<&FleesBelow>)>::query()
+        .filter(component::<Enemy>() & component::<MyTurn>() & !component::<Stunned>());
     
-    let enemies_to_process: Vec<(Entity, Point, i32)> = enemy_query
+    let mut enemies_to_process: Vec<(Entity, Point, Behaviour, Awareness, String, bool)> = enemy_query
         .iter(ecs)
-        .map(|(entity, pos, follower)| (*entity, *pos, follower.move_timer))
+        .map(|(entity, pos, behaviour, awareness, health, name, flees_below)| {
+            let fleeing = flees_below.map_or(false, |below| health.current <= below.0);
+            (*entity, *pos, *behaviour, *awareness, name.0.clone(), fleeing)
+        })
         .collect();
+    // The closest get first pick of the tiles around the player
+    enemies_to_process.sort_by_key(|(_, pos, ..)| chase_map.distance(map, *pos).unwrap_or(UNREACHABLE));
+    let mut hunt = Hunt {
+        map,
+        chase_map,
+        player_pos,
+        player_entity,
+        open_slots: map.topology.steps()
+            .iter()
+            .map(|step| player_pos + *step)
+            .filter(|tile| map.can_enter_tile(*tile) && !enemy_positions.contains(tile))
+            .collect(),
+    };
     
-    for (entity, pos, move_timer) in enemies_to_process {
-        if move_timer <= 0 {
-            let new_follower = FollowsPlayer { move_timer: 1 };
-            commands.add_component(entity, new_follower);
-            
-            let distance_to_player = (pos.x - player_pos.x).abs() + (pos.y - player_pos.y).abs();
-            if distance_to_player == 1 {
+    for (entity, pos, behaviour, awareness, name, fleeing) in enemies_to_process {
+        let distance = DistanceAlg::Pythagoras.distance2d(pos, player_pos);
+        let sees_player = distance <= awareness.sight as f32 && map.has_line_of_sight(pos, player_pos);
+        let mut state = match awareness.state {
+            _ if sees_player => Alertness::Alert { last_seen: player_pos },
+            Alertness::Alert { last_seen } => Alertness::Searching { last_seen },
+            // Nobody there, so it gives up
+            Alertness::Searching { last_seen } if last_seen == pos => Alertness::Unaware,
+            unchanged => unchanged,
+        };
+        if awareness.state == Alertness::Unaware && sees_player {
+            game_log.add(LogCategory::Combat, format!("The {} spots you", name));
+        }
+
+        let adjacent_ally = allies
+            .iter()
+            .find(|(_, ally_pos)| map.topology.is_adjacent(pos, *ally_pos));
+        // Anything summoned within reach gets hit when there's nothing better to do
+        let hit_ally = adjacent_ally.map(|(ally, _)| Intent::Attack(*ally));
+        let retreat = || flee_map.downhill(map, pos, |tile| enemy_positions.contains(&tile) || tile == player_pos);
+        // None when there's no way to where the player was last seen
+        let search = |last_seen: Point| match hit_ally {
+            Some(attack) => Some(Some(attack)),
+            None => step_towards(map, pos, last_seen, &enemy_positions).map(|step| step.map(Intent::Step)),
+        };
+
+        let action = match (behaviour, state) {
+            // Runs while it can see the player, and hides in place once it can't: the flee map
+            // comes from where the player really is, which an unseeing monster can't know
+            _ if fleeing && !sees_player => None,
+            _ if fleeing => match retreat() {
+                Some(step) => Some(Intent::Step(step)),
+                // Cornered, it fights back
+                None if map.topology.is_adjacent(pos, player_pos) => Some(Intent::Attack(player_entity)),
+                None => None,
+            },
+            (Behaviour::Guard { post, radius }, Alertness::Alert { .. })
+                if DistanceAlg::Pythagoras.distance2d(post, player_pos) <= radius as f32 => {
+                hunt.chase(pos, &enemy_positions, adjacent_ally)
+            }
+            (Behaviour::Guard { post, radius }, Alertness::Searching { last_seen })
+                if DistanceAlg::Pythagoras.distance2d(post, last_seen) <= radius as f32 => search(last_seen).flatten(),
+            (Behaviour::Guard { post, .. }, _) => {
+                // Too far from its post to go looking
+                if let Alertness::Searching { .. } = state {
+                    state = Alertness::Unaware;
+                }
+                hit_ally.or_else(|| step_towards(map, pos, post, &enemy_positions).flatten().map(Intent::Step))
+            }
+            (Behaviour::Ranged { range }, Alertness::Alert { .. }) => {
+                match retreat().filter(|_| distance < TOO_CLOSE) {
+                    Some(step) => Some(Intent::Step(step)),
+                    None if distance <= range as f32 => Some(Intent::Attack(player_entity)),
+                    // Closing in for a shot needs no spot beside the player
+                    None => hit_ally.or_else(|| hunt.approach(pos, &enemy_positions)),
+                }
+            }
+            (_, Alertness::Alert { .. }) => hunt.chase(pos, &enemy_positions, adjacent_ally),
+            (_, Alertness::Searching { last_seen }) => match search(last_seen) {
+                // Blocked for now, it waits its turn and keeps looking
+                Some(action) => action,
+                None => {
+                    state = Alertness::Unaware;
+                    None
+                }
+            },
+            (Behaviour::Wanderer, Alertness::Unaware) => {
+                hit_ally.or_else(|| wander(map, rng, pos, &enemy_positions, player_pos).map(Intent::Step))
+            }
+            (Behaviour::Ranged { .. }, Alertness::Unaware) => hit_ally,
+        };
+
+        if state != awareness.state {
+            commands.add_component(entity, Awareness { state, ..awareness });
+        }
+
+        match action {
+            Some(Intent::Attack(victim)) => {
                 commands.push(((), WantsToAttack {
                     attacker: entity,
-                    victim: player_entity,
+                    victim,
                 }));
-            } else {
-                if let Some(best_move) = find_best_move(&flow_field, map, pos, &enemy_positions, player_pos) {
-                    commands.push(((), WantsToMove {
-                        entity,
-                        destination: best_move,
-                    }));
-                }
             }
-        } else {
-            let new_follower = FollowsPlayer {
-                move_timer: move_timer - 1,
-            };
-            commands.add_component(entity, new_follower);
+            Some(Intent::Step(destination)) => {
+                // Later movers this turn mustn't pick the same tile
+                enemy_positions.retain(|tile| *tile != pos);
+                enemy_positions.push(destination);
+                commands.push(((), WantsToMove {
+                    entity,
+                    destination,
+                }));
+            }
+            None => {}
         }
     }
 }
 
-fn create_flow_field(map: &Map, player_pos: Point) -> Vec<Vec<i32>> {
-    let mut distances = vec![vec![-1; SCREEN_HEIGHT as usize]; SCREEN_WIDTH as usize];
-    let mut queue = VecDeque::new();
-    
-    let target_positions = [
-        Point::new(player_pos.x, player_pos.y - 1),
-        Point::new(player_pos.x + 1, player_pos.y),
-        Point::new(player_pos.x, player_pos.y + 1),
-        Point::new(player_pos.x - 1, player_pos.y),
-    ];
-    
-    distances[player_pos.x as usize][player_pos.y as usize] = -2;
-    
-    for target_pos in target_positions.iter() {
-        if map.in_bounds(*target_pos) && map.can_enter_tile(*target_pos) {
-            distances[target_pos.x as usize][target_pos.y as usize] = 0;
-            queue.push_back(*target_pos);
+// What every monster closing in on the player this pass shares
+struct Hunt<'a> {
+    map: &'a Map,
+    chase_map: &'a DistanceMap,
+    player_pos: Point,
+    player_entity: Entity,
+    // Free tiles beside the player that nobody has claimed yet
+    open_slots: Vec<Point>,
+}
+
+impl Hunt<'_> {
+    // Melee whatever is in reach, the player first, or make for a tile beside them nobody else
+    // has claimed, around the crowd if there's another way. Once all are taken, close in over
+    // open ground and wait at corridor mouths rather than queue up inside
+    fn chase(&mut self, pos: Point, enemy_positions: &[Point], adjacent_ally: Option<&(Entity, Point)>) -> Option<Intent> {
+        let map = self.map;
+        if map.topology.is_adjacent(pos, self.player_pos) {
+            return Some(Intent::Attack(self.player_entity));
         }
-    }
-    
-    let directions = [
-        Point::new(-1, -1), Point::new(0, -1), Point::new(1, -1),
-        Point::new(-1,  0),                     Point::new(1,  0),
-        Point::new(-1,  1), Point::new(0,  1), Point::new(1,  1),
-    ];
-    
-    while let Some(current) = queue.pop_front() {
-        let current_distance = distances[current.x as usize][current.y as usize];
-        
-        for direction in directions.iter() {
-            let next_pos = current + *direction;
-            
-            if map.in_bounds(next_pos) && map.can_enter_tile(next_pos) {
-                let next_x = next_pos.x as usize;
-                let next_y = next_pos.y as usize;
-                
-                if distances[next_x][next_y] == -1 {
-                    distances[next_x][next_y] = current_distance + 1;
-                    queue.push_back(next_pos);
-                }
+        if let Some((ally, _)) = adjacent_ally {
+            // Anything summoned in the way gets hit before the player
+            return Some(Intent::Attack(*ally));
+        }
+
+        let mut slots: Vec<Point> = self.open_slots
+            .iter()
+            .filter(|slot| self.chase_map.distance(map, **slot).is_some())
+            .copied()
+            .collect();
+        slots.sort_by_key(|slot| DistanceAlg::PythagorasSquared.distance2d(pos, *slot) as i32);
+        let mut crowd = enemy_positions.to_vec();
+        crowd.push(self.player_pos);
+        let crowded = CrowdedMap { map, crowd: &crowd };
+        for slot in slots {
+            let path = a_star_search(map.point2d_to_index(pos), map.point2d_to_index(slot), &crowded);
+            if path.success && path.steps.len() > 1 {
+                self.open_slots.retain(|open| *open != slot);
+                // Someone is still in the way; wait for them to move on
+                let step = map.index_to_point2d(path.steps[1]);
+                return Some(Intent::Step(step)).filter(|_| !enemy_positions.contains(&step));
             }
         }
+
+        // One already inside a corridor keeps going rather than stopping up everyone behind it
+        let entering_corridor = |step: Point| !in_corridor(map, pos) && in_corridor(map, step);
+        match self.chase_map.distance(map, pos) {
+            Some(distance) if distance > HOLD_BACK => self.approach(pos, enemy_positions)
+                .filter(|intent| !matches!(intent, Intent::Step(step) if entering_corridor(*step))),
+            _ => None,
+        }
+    }
+
+    // Straight down the chase map, for monsters that needn't stand beside the player
+    fn approach(&self, pos: Point, enemy_positions: &[Point]) -> Option<Intent> {
+        self.chase_map
+            .downhill(self.map, pos, |tile| enemy_positions.contains(&tile) || tile == self.player_pos)
+            .map(Intent::Step)
     }
-    
-    distances
 }
 
-fn find_best_move(
-    flow_field: &Vec<Vec<i32>>, 
-    map: &Map, 
-    enemy_pos: Point, 
-    enemy_positions: &[Point],
-    player_pos: Point
-) -> Option<Point> {
-    let current_distance = flow_field[enemy_pos.x as usize][enemy_pos.y as usize];
-    
-    if current_distance == -1 {
-        return None;
+// The map as seen on the way to a claimed tile: a creature in the way makes a route dearer
+// instead of closing it, so monsters spread out over other routes when there are any
+struct CrowdedMap<'a> {
+    map: &'a Map,
+    crowd: &'a [Point],
+}
+
+impl Algorithm2D for CrowdedMap<'_> {
+    fn dimensions(&self) -> Point {
+        self.map.dimensions()
     }
-    
-    if current_distance == 0 {
-        return None;
+}
+
+impl BaseMap for CrowdedMap<'_> {
+    fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
+        let mut exits = self.map.get_available_exits(idx);
+        exits
+            .iter_mut()
+            .filter(|(exit, _)| self.crowd.contains(&self.map.index_to_point2d(*exit)))
+            .for_each(|(_, cost)| *cost += CROWD_COST);
+        exits
     }
-    
-    let directions = [
-        Point::new(-1, -1), Point::new(0, -1), Point::new(1, -1),
-        Point::new(-1,  0),                     Point::new(1,  0),
-        Point::new(-1,  1), Point::new(0,  1), Point::new(1,  1),
-    ];
-    
-    let mut best_move = None;
-    let mut best_distance = current_distance;
-    
-    for direction in directions.iter() {
-        let next_pos = enemy_pos + *direction;
-        
-        if map.in_bounds(next_pos) && 
-           map.can_enter_tile(next_pos) && 
-           !enemy_positions.contains(&next_pos) &&
-           next_pos != player_pos {
-            
-            let next_distance = flow_field[next_pos.x as usize][next_pos.y as usize];
-            
-            if next_distance != -1 && next_distance != -2 && next_distance < best_distance {
-                best_distance = next_distance;
-                best_move = Some(next_pos);
-            }
-        }
+
+    fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
+        self.map.get_pathing_distance(idx1, idx2)
     }
-    
-    best_move
-}
\ No newline at end of file
+}
+
+// Walled in on two opposite sides
+fn in_corridor(map: &Map, pos: Point) -> bool {
+    let blocked = |x: i32, y: i32| !map.can_enter_tile(pos + Point::new(x, y));
+    (blocked(-1, 0) && blocked(1, 0)) || (blocked(0, -1) && blocked(0, 1))
+}
+
+fn wander(map: &Map, rng: &mut RandomNumberGenerator, pos: Point, enemy_positions: &[Point], player_pos: Point) -> Option<Point> {
+    let steps = map.topology.steps();
+    let destination = pos + steps[rng.range(0, steps.len())];
+    if map.can_enter_tile(destination) && !enemy_positions.contains(&destination) && destination != player_pos {
+        Some(destination)
+    } else {
+        None
+    }
+}
+
+// The first step of the shortest walk to `goal`: None when there's no way there at all, and
+// Some(None) when already there or someone is standing in the way for now
+fn step_towards(map: &Map, pos: Point, goal: Point, enemy_positions: &[Point]) -> Option<Option<Point>> {
+    if pos == goal {
+        return Some(None);
+    }
+    let path = a_star_search(map.point2d_to_index(pos), map.point2d_to_index(goal), map);
+    if !path.success || path.steps.len() < 2 {
+        return None;
+    }
+    let step = map.index_to_point2d(path.steps[1]);
+    Some(Some(step).filter(|step| !enemy_positions.contains(step)))
+}
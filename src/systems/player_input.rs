@@ -1,110 +1,80 @@
 use crate::prelude::*;
 
+// How many frames a lone straight move waits for a perpendicular arrow to join it
+const ARROW_GRACE_FRAMES: u32 = 4;
+
 #[system]
 #[read_component(Point)]
-#[read_component(CanDash)]
-#[read_component(CanCastFireball)]
+#[read_component(Abilities)]
 #[read_component(Mana)]
 #[read_component(Player)]
 #[read_component(Enemy)]
+#[read_component(Stunned)]
+#[read_component(Item)]
+#[read_component(Carried)]
+#[read_component(Name)]
+#[read_component(Ally)]
+#[read_component(FieldOfView)]
+#[read_component(Travelling)]
 pub fn player_input(
     ecs: &SubWorld,
     commands: &mut CommandBuffer,
     #[resource] key: &Option<VirtualKeyCode>,
-    #[resource] mouse_buttons: &Option<(i32, i32, bool, bool, bool)>,
-    #[resource] mouse_pos: &Point,
-    #[resource] camera: &Camera,
+    #[resource] bindings: &Bindings,
+    #[resource] held: &HeldKeys,
     #[resource] turn_state: &mut TurnState,
     #[resource] targeting_state: &mut TargetingState,
-    #[resource] map: &Map
+    #[resource] map: &Map,
+    #[resource] game_log: &mut GameLog,
+    #[state] waiting: &mut Option<(VirtualKeyCode, u32)>
 ) {
-    let mut players = <(Entity, &Point, &CanDash, &CanCastFireball, &Mana)>::query()
+    // Aiming keys belong to targeting_input, and travel may have taken this turn or be
+    // swallowing the key that stops it
+    if targeting_state.is_targeting() || *turn_state != TurnState::AwaitingInput {
+        *waiting = None;
+        return;
+    }
+
+    let mut players = <(Entity, &Point, &Abilities, &Mana)>::query()
         .filter(component::<Player>());
 
-    if let Some((player_entity, player_point, dash_ability, fireball_ability, mana)) = players.iter(ecs).nth(0) {
+    if let Some((player_entity, player_point, abilities, mana)) = players.iter(ecs).nth(0) {
         let player_entity = *player_entity;
-        let player_point = *player_point;
-
-        if let Some((_, _, left_click, right_click, _)) = *mouse_buttons {
-            // Right-click cancels targeting
-            if right_click {
-                if targeting_state.is_targeting() {
-                    *targeting_state = TargetingState::None;
-                }
-                return;
-            }
-            
-            if left_click && targeting_state.is_targeting() {
-                let offset = Point::new(camera.left_x, camera.top_y);
-                let world_target = *mouse_pos + offset;
-                
-                if !map.can_enter_tile(world_target) {
-                    return;
-                }
-                
-                match *targeting_state {
-                    TargetingState::SelectingDashTarget => {
-                        if mana.current >= dash_ability.cost {
-                            let distance = DistanceAlg::Pythagoras.distance2d(player_point, world_target);
-                            
-                            if distance <= dash_ability.range as f32 {
-                                if has_clear_path(map, player_point, world_target) {
-                                    commands.push(((), WantsToUseDashToPoint {
-                                        entity: player_entity,
-                                        target: world_target,
-                                    }));
-                                    
-                                    *turn_state = TurnState::PlayerTurn;
-                                    *targeting_state = TargetingState::None;
-                                }
-                            }
-                        }
-                    }
-                    
-                    TargetingState::SelectingFireballTarget => {
-                        if mana.current >= fireball_ability.cost {
-                            let distance = DistanceAlg::Pythagoras.distance2d(player_point, world_target);
-                            
-                            if distance <= fireball_ability.range as f32 && 
-                               has_line_of_sight(map, player_point, world_target) {
-                                commands.push(((), WantsToUseFireball {
-                                    entity: player_entity,
-                                    target: world_target,
-                                }));
-                                
-                                *turn_state = TurnState::PlayerTurn;
-                                *targeting_state = TargetingState::None;
-                            }
-                        }
-                    }
-                    
-                    _ => {}
-                }
-            }
+        let travelling = ecs.entry_ref(player_entity)
+            .map_or(false, |entry| entry.get_component::<Travelling>().is_ok());
+        if travelling {
+            *waiting = None;
+            return;
         }
-
-        if let Some(key) = *key {
-            if targeting_state.is_targeting() {
-                match key {
-                    VirtualKeyCode::Escape => {
-                        *targeting_state = TargetingState::None;
+        let player_point = *player_point;
+        let stunned = ecs.entry_ref(player_entity)
+            .map_or(false, |entry| entry.get_component::<Stunned>().is_ok());
+
+        // Two held arrows only make a diagonal when diagonals are allowed at all
+        let action = match map.topology {
+            Topology::EightWay => with_arrow_diagonals(*key, bindings, held, waiting),
+            Topology::FourWay => key.and_then(|key| bindings.action(key)),
+        };
+        if action.is_some() {
+
+            // A stunned player can only let the turn pass, by waiting or trying to move or attack,
+            // or look back over the log
+            if stunned {
+                match action {
+                    Some(Action::Log) => {}
+                    Some(action) if action == Action::Wait || action.direction().is_some() => {
+                        *turn_state = TurnState::PlayerTurn;
                         return;
                     }
-                    _ => {}
+                    _ => return,
                 }
-                return;
             }
+            match action {
+                // Diagonal keys do nothing on a four-way map
+                Some(action) if action.direction().map_or(false, |step| !map.topology.allows(step)) => {}
 
-            match key {
-                VirtualKeyCode::Left | VirtualKeyCode::Right | 
-                VirtualKeyCode::Up | VirtualKeyCode::Down => {
-                    let delta = match key {
-                        VirtualKeyCode::Left => Point::new(-1, 0),
-                        VirtualKeyCode::Right => Point::new(1, 0),
-                        VirtualKeyCode::Up => Point::new(0, -1),
-                        VirtualKeyCode::Down => Point::new(0, 1),   
-                        _ => Point::new(0, 0),
-                    };
+                Some(action) if action.direction().is_some() => {
+                    let delta = action.direction().unwrap_or_else(Point::zero);
 
                     let mut monsters = <(Entity, &Point)>::query().filter(component::<Enemy>());
                     let destination = player_point + delta;
@@ -125,100 +95,159 @@ pub fn player_input(
                             entity: player_entity, 
                             destination: destination
                         }));
+                        // Walking into a summoned ally swaps places with it
+                        let mut allies = <(Entity, &Point)>::query().filter(component::<Ally>());
+                        if let Some((ally, _)) = allies.iter(ecs).find(|(_, ally_pos)| **ally_pos == destination) {
+                            commands.push(((), WantsToMove{
+                                entity: *ally,
+                                destination: player_point
+                            }));
+                        }
                     }
                     
                     *turn_state = TurnState::PlayerTurn;
                 }
                 
-                VirtualKeyCode::Space => {
+                Some(Action::Wait) => {
                     *turn_state = TurnState::PlayerTurn;
                 }
                 
-                VirtualKeyCode::D => {
-                    if mana.current >= dash_ability.cost {
-                        *targeting_state = TargetingState::SelectingDashTarget;
-                    }
+                Some(Action::Log) => {
+                    *turn_state = TurnState::ViewingLog;
                 }
                 
-                VirtualKeyCode::F => {
-                    if mana.current >= fireball_ability.cost {
-                        *targeting_state = TargetingState::SelectingFireballTarget;
+                Some(Action::Inventory) => {
+                    *turn_state = TurnState::ShowingInventory;
+                }
+                
+                Some(Action::PickUp) => {
+                    let carried = <&Carried>::query()
+                        .iter(ecs)
+                        .filter(|carried| carried.0 == player_entity)
+                        .count();
+                    let mut items = <(Entity, &Point, &Name)>::query()
+                        .filter(component::<Item>());
+                    if let Some((item_entity, _, name)) = items
+                        .iter(ecs)
+                        .find(|(_, item_pos, _)| **item_pos == player_point)
+                    {
+                        if carried < MAX_CARRIED {
+                            commands.remove_component::<Point>(*item_entity);
+                            commands.add_component(*item_entity, Carried(player_entity));
+                            game_log.add(LogCategory::Item, format!("Player picks up a {}", name.0));
+                            *turn_state = TurnState::PlayerTurn;
+                        } else {
+                            game_log.add(LogCategory::Item, "Your pack is full");
+                        }
                     }
                 }
                 
+                Some(Action::CastAbility(index)) => {
+                    if let Some(ability) = abilities.0.get(index) {
+                        if mana.current < ability.cost {
+                            game_log.add(LogCategory::Spell, format!("Not enough mana for {}", ability.name));
+                        } else if !ability.is_ready() {
+                            game_log.add(LogCategory::Spell, format!("{} is still recharging", ability.name));
+                        } else {
+                            // Aim at the closest enemy straight away, if there is one
+                            let cursor = super::targeting_input::targets_in_range(ecs, player_entity, player_point, ability)
+                                .first()
+                                .copied()
+                                .unwrap_or(player_point);
+                            *targeting_state = TargetingState::Selecting { ability: index, cursor };
+                        }
+                    }
+                }
+
+                // Aiming and cancelling only mean something while targeting
                 _ => {}
             }
         }
     }
 }
 
-fn has_clear_path(map: &Map, start: Point, end: Point) -> bool {
-    let mut current = start;
-    let dx = (end.x - start.x).abs();
-    let dy = (end.y - start.y).abs();
-    let sx = if start.x < end.x { 1 } else { -1 };
-    let sy = if start.y < end.y { 1 } else { -1 };
-    let mut err = dx - dy;
-
-    loop {
-        if current != start {
-            if current == end {
-                return true;
+// Pressing two arrows together never lands on the same frame, so a straight move is held back
+// until its key comes up or a few frames pass. A perpendicular arrow pressed meanwhile turns
+// the pair into one diagonal step rather than a straight step followed by a diagonal
+fn with_arrow_diagonals(
+    key: Option<VirtualKeyCode>,
+    bindings: &Bindings,
+    held: &HeldKeys,
+    waiting: &mut Option<(VirtualKeyCode, u32)>
+) -> Option<Action> {
+    match (key, waiting.take()) {
+        (Some(key), Some((first, _))) => {
+            let mut together = held.clone();
+            together.0.insert(first);
+            match bindings.action_with_held(key, &together) {
+                Some(action) if Action::DIAGONAL.contains(&action) => Some(action),
+                // Not a partner for it, so the first move goes ahead on its own
+                _ => bindings.action(first),
             }
-            
-            if !map.can_enter_tile(current) {
-                return false;
+        }
+        (Some(key), None) => match bindings.action_with_held(key, held) {
+            Some(action) if Action::ORTHOGONAL.contains(&action) => {
+                *waiting = Some((key, 0));
+                None
             }
+            action => action,
+        },
+        (None, Some((first, frames))) if frames < ARROW_GRACE_FRAMES && held.0.contains(&first) => {
+            *waiting = Some((first, frames + 1));
+            None
         }
+        (None, Some((first, _))) => bindings.action(first),
+        (None, None) => None,
+    }
+}
 
-        let e2 = 2 * err;
-        if e2 > -dy {
-            err -= dy;
-            current.x += sx;
-        }
-        if e2 < dx {
-            err += dx;
-            current.y += sy;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
 
-        if current == end {
-            return true;
-        }
+    fn held(keys: &[VirtualKeyCode]) -> HeldKeys {
+        HeldKeys(keys.iter().copied().collect::<HashSet<_>>())
     }
-}
 
-// Bresenham's algorithm
-fn has_line_of_sight(map: &Map, start: Point, end: Point) -> bool {
-    let mut current = start;
-    let dx = (end.x - start.x).abs();
-    let dy = (end.y - start.y).abs();
-    let sx = if start.x < end.x { 1 } else { -1 };
-    let sy = if start.y < end.y { 1 } else { -1 };
-    let mut err = dx - dy;
-
-    loop {
-        if current != start {
-            if current == end {
-                return true;
-            }
-            
-            if !map.can_enter_tile(current) {
-                return false;
-            }
-        }
+    #[test]
+    fn two_arrows_make_one_diagonal_step() {
+        let bindings = Bindings::load(&[]);
+        let mut waiting = None;
+        let up = VirtualKeyCode::Up;
+        let right = VirtualKeyCode::Right;
+
+        assert_eq!(with_arrow_diagonals(Some(up), &bindings, &held(&[up]), &mut waiting), None);
+        assert_eq!(with_arrow_diagonals(None, &bindings, &held(&[up]), &mut waiting), None);
+        let action = with_arrow_diagonals(Some(right), &bindings, &held(&[up, right]), &mut waiting);
+        assert_eq!(action, Some(Action::MoveNorthEast));
+        assert_eq!(waiting, None);
+    }
 
-        let e2 = 2 * err;
-        if e2 > -dy {
-            err -= dy;
-            current.x += sx;
-        }
-        if e2 < dx {
-            err += dx;
-            current.y += sy;
-        }
+    #[test]
+    fn a_lone_arrow_moves_once_released_or_after_a_moment() {
+        let bindings = Bindings::load(&[]);
+        let mut waiting = None;
+        let up = VirtualKeyCode::Up;
+
+        with_arrow_diagonals(Some(up), &bindings, &held(&[up]), &mut waiting);
+        assert_eq!(with_arrow_diagonals(None, &bindings, &held(&[]), &mut waiting), Some(Action::MoveNorth));
 
-        if current == end {
-            return true;
+        with_arrow_diagonals(Some(up), &bindings, &held(&[up]), &mut waiting);
+        let mut moves = Vec::new();
+        for _ in 0..=ARROW_GRACE_FRAMES {
+            moves.push(with_arrow_diagonals(None, &bindings, &held(&[up]), &mut waiting));
         }
+        assert_eq!(moves.iter().flatten().collect::<Vec<_>>(), [&Action::MoveNorth]);
+        assert_eq!(moves.last(), Some(&Some(Action::MoveNorth)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn other_keys_act_at_once() {
+        let bindings = Bindings::load(&[]);
+        let mut waiting = None;
+        let keys = [VirtualKeyCode::Space, VirtualKeyCode::Numpad9];
+        assert_eq!(with_arrow_diagonals(Some(keys[0]), &bindings, &held(&keys[..1]), &mut waiting), Some(Action::Wait));
+        assert_eq!(with_arrow_diagonals(Some(keys[1]), &bindings, &held(&keys[1..]), &mut waiting), Some(Action::MoveNorthEast));
+    }
+}
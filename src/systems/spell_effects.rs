@@ -0,0 +1,29 @@
+use crate::prelude::*;
+
+#[system(for_each)]
+pub fn spell_effects(
+    entity: &Entity,
+    effect: &SpellEffect,
+    #[resource] camera: &Camera,
+    commands: &mut CommandBuffer
+) {
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.target(1);
+    let offset = Point::new(camera.left_x, camera.top_y);
+    
+    for pos in effect.tiles.iter() {
+        let screen_pos = *pos - offset;
+        // Position is on screen?
+        if screen_pos.x >= 0 && screen_pos.x < DISPLAY_WIDTH && 
+           screen_pos.y >= 0 && screen_pos.y < DISPLAY_HEIGHT {
+            draw_batch.set(
+                screen_pos,
+                ColorPair::new(effect.color, RED),
+                to_cp437('*')
+            );
+        }
+    }
+    
+    draw_batch.submit(6000).expect("Batch error");
+    commands.remove(*entity);
+}
\ No newline at end of file
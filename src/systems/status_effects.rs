@@ -0,0 +1,139 @@
+use crate::prelude::*;
+
+// Runs in both the player and monster schedules, ticking only whoever just took a turn
+#[system]
+#[read_component(Player)]
+#[read_component(Health)]
+#[read_component(Name)]
+#[read_component(EnemyStats)]
+#[read_component(Burning)]
+#[read_component(Poisoned)]
+#[read_component(Stunned)]
+#[read_component(Slowed)]
+#[read_component(Hasted)]
+#[read_component(Regenerating)]
+#[read_component(MyTurn)]
+pub fn status_effects(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] turn_state: &TurnState,
+    #[resource] game_log: &mut GameLog
+) {
+    let players_turn = *turn_state == TurnState::PlayerTurn;
+    let acting: Vec<Entity> = <Entity>::query()
+        .filter(component::<MyTurn>())
+        .iter(ecs)
+        .copied()
+        .collect();
+    let took_turn = |entity: &Entity, player: Option<&Player>| if players_turn {
+        player.is_some()
+    } else {
+        player.is_none() && acting.contains(entity)
+    };
+
+    // Effects that do nothing but wear off
+    let mut timed = <(Entity, Option<&Player>, Option<&Stunned>, Option<&Slowed>, Option<&Hasted>)>::query();
+    timed
+        .iter(ecs)
+        .filter(|(entity, player, ..)| took_turn(entity, *player))
+        .for_each(|(entity, _, stunned, slowed, hasted)| {
+            if let Some(stunned) = stunned {
+                if stunned.turns > 1 {
+                    commands.add_component(*entity, Stunned{ turns: stunned.turns - 1 });
+                } else {
+                    commands.remove_component::<Stunned>(*entity);
+                }
+            }
+
+            if let Some(slowed) = slowed {
+                if slowed.turns > 1 {
+                    commands.add_component(*entity, Slowed{ turns: slowed.turns - 1 });
+                } else {
+                    commands.remove_component::<Slowed>(*entity);
+                }
+            }
+
+            if let Some(hasted) = hasted {
+                if hasted.turns > 1 {
+                    commands.add_component(*entity, Hasted{ turns: hasted.turns - 1 });
+                } else {
+                    commands.remove_component::<Hasted>(*entity);
+                }
+            }
+        });
+
+    // Only the player's spells set monsters burning, so theirs is the experience when one dies of it
+    let player_entity = <Entity>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .copied();
+
+    let mut affected = <(
+        Entity,
+        Option<&Player>,
+        Option<&Health>,
+        Option<&Burning>,
+        Option<&Poisoned>,
+        Option<&Regenerating>
+    )>::query();
+
+    affected
+        .iter(ecs)
+        .filter(|(entity, player, ..)| took_turn(entity, *player))
+        .for_each(|(entity, player, health, burning, poisoned, regenerating)| {
+            let mut health_change = 0;
+
+            if let Some(burning) = burning {
+                health_change -= burning.damage;
+                if burning.turns > 1 {
+                    commands.add_component(*entity, Burning{ turns: burning.turns - 1, ..*burning });
+                } else {
+                    commands.remove_component::<Burning>(*entity);
+                }
+            }
+
+            if let Some(poisoned) = poisoned {
+                health_change -= poisoned.damage;
+                if poisoned.turns > 1 {
+                    commands.add_component(*entity, Poisoned{ turns: poisoned.turns - 1, ..*poisoned });
+                } else {
+                    commands.remove_component::<Poisoned>(*entity);
+                }
+            }
+
+            if let Some(regenerating) = regenerating {
+                health_change += regenerating.amount;
+                if regenerating.turns > 1 {
+                    commands.add_component(*entity, Regenerating{ turns: regenerating.turns - 1, ..*regenerating });
+                } else {
+                    commands.remove_component::<Regenerating>(*entity);
+                }
+            }
+
+            if let Some(health) = health {
+                if health_change != 0 {
+                    let new_health = Health {
+                        current: (health.current + health_change).clamp(0, health.max),
+                        max: health.max,
+                    };
+                    // As in combat, a dying player stays put for end_turn to notice
+                    if new_health.current <= 0 && player.is_none() {
+                        commands.remove(*entity);
+                        let entry = ecs.entry_ref(*entity).unwrap();
+                        if let Ok(name) = entry.get_component::<Name>() {
+                            game_log.add(LogCategory::Kill, format!("{} succumbs to its wounds", name.0));
+                        }
+                        if let (Some(player_entity), Ok(enemy_stats)) = (player_entity, entry.get_component::<EnemyStats>()) {
+                            commands.push(((), GainExperience {
+                                entity: player_entity,
+                                amount: enemy_stats.enemy_type.xp(),
+                            }));
+                        }
+                    } else {
+                        commands.add_component(*entity, new_health);
+                    }
+                }
+            }
+        });
+}
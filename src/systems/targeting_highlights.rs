@@ -3,13 +3,13 @@ use crate::prelude::*;
 #[system]
 #[read_component(Player)]
 #[read_component(Point)]
-#[read_component(CanDash)]
-#[read_component(CanCastFireball)]
+#[read_component(Abilities)]
 #[read_component(Mana)]
+#[read_component(Enemy)]
+#[read_component(Ally)]
 pub fn targeting_highlights(
     ecs: &SubWorld,
     #[resource] targeting_state: &TargetingState,
-    #[resource] mouse_pos: &Point,
     #[resource] camera: &Camera,
     #[resource] map: &Map
 ) {
@@ -17,167 +17,136 @@ pub fn targeting_highlights(
     draw_batch.target(3);
     let offset = Point::new(camera.left_x, camera.top_y);
     
-    let world_mouse_pos = *mouse_pos + offset;
-    
-    
-    if !targeting_state.is_targeting() {
-        draw_batch.submit(7000).expect("Batch error");
-        return;
-    }
+    let (selected, cursor) = match (targeting_state.ability(), targeting_state.cursor()) {
+        (Some(selected), Some(cursor)) => (selected, cursor),
+        _ => {
+            draw_batch.submit(7000).expect("Batch error");
+            return;
+        }
+    };
 
-    let mut player_query = <(&Point, &CanDash, &CanCastFireball, &Mana)>::query()
+    let mut player_query = <(&Point, &Abilities, &Mana)>::query()
         .filter(component::<Player>());
     
-    if let Some((player_pos, dash_ability, fireball_ability, mana)) = player_query.iter(ecs).nth(0) {
-        match targeting_state {
-            TargetingState::SelectingDashTarget => {
-                if mana.current >= dash_ability.cost {
-                    for y in (player_pos.y - dash_ability.range)..=(player_pos.y + dash_ability.range) {
-                        for x in (player_pos.x - dash_ability.range)..=(player_pos.x + dash_ability.range) {
-                            let target_pos = Point::new(x, y);
-                            let distance = DistanceAlg::Pythagoras.distance2d(*player_pos, target_pos);
-                            
-                            if distance <= dash_ability.range as f32 && distance > 0.0 {
-                                if map.can_enter_tile(target_pos) && 
-                                   has_clear_path_for_dash(map, *player_pos, target_pos) {
-                                    let screen_pos = target_pos - offset;
-                                    if screen_pos.x >= 0 && screen_pos.x < DISPLAY_WIDTH && 
-                                       screen_pos.y >= 0 && screen_pos.y < DISPLAY_HEIGHT {
-                                        draw_batch.set(
-                                            screen_pos,
-                                            ColorPair::new(CYAN, BLACK),
-                                            to_cp437('·')
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            
-            TargetingState::SelectingFireballTarget => {
-                if mana.current >= fireball_ability.cost {
-                    for y in (player_pos.y - fireball_ability.range)..=(player_pos.y + fireball_ability.range) {
-                        for x in (player_pos.x - fireball_ability.range)..=(player_pos.x + fireball_ability.range) {
-                            let target_pos = Point::new(x, y);
-                            let distance = DistanceAlg::Pythagoras.distance2d(*player_pos, target_pos);
-                            
-                            if distance <= fireball_ability.range as f32 && distance > 0.0 {
-                                if map.can_enter_tile(target_pos) && 
-                                   has_line_of_sight(map, *player_pos, target_pos) {
-                                    let screen_pos = target_pos - offset;
-                                    if screen_pos.x >= 0 && screen_pos.x < DISPLAY_WIDTH && 
-                                       screen_pos.y >= 0 && screen_pos.y < DISPLAY_HEIGHT {
-                                        
-                                        let blast_distance = DistanceAlg::Pythagoras.distance2d(world_mouse_pos, target_pos);
-                                        
-                                        let (color, glyph) = if blast_distance <= 1.5 {
-                                            if map.can_enter_tile(world_mouse_pos) && 
-                                               has_line_of_sight(map, *player_pos, world_mouse_pos) {
-                                                (ColorPair::new(ORANGE, BLACK), to_cp437('*'))
-                                            } else {
-                                                (ColorPair::new(RED, BLACK), to_cp437('x'))
-                                            }
-                                        } else {
-                                            (ColorPair::new(YELLOW, BLACK), to_cp437('·'))
-                                        };
-                                        
-                                        draw_batch.set(screen_pos, color, glyph);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    
-                    if map.can_enter_tile(world_mouse_pos) && 
-                       has_line_of_sight(map, *player_pos, world_mouse_pos) {
-                        let distance = DistanceAlg::Pythagoras.distance2d(*player_pos, world_mouse_pos);
-                        if distance <= fireball_ability.range as f32 {
-                            draw_line_of_sight(*player_pos, world_mouse_pos, offset, &mut draw_batch);
-                        }
-                    }
+    if let Some((player_pos, abilities, mana)) = player_query.iter(ecs).nth(0) {
+        if let Some(ability) = abilities.0.get(selected) {
+            if mana.current >= ability.cost {
+                let creatures = Creatures::gather(ecs);
+                match ability.targeting {
+                    Targeting::Path | Targeting::Blast { .. } =>
+                        highlight_aimed(ability, map, &creatures, *player_pos, cursor, offset, &mut draw_batch),
+                    Targeting::Chain { .. } =>
+                        highlight_chain(ability, map, &creatures, *player_pos, cursor, offset, &mut draw_batch),
+                    Targeting::Caster { .. } =>
+                        highlight_caster(ability, map, &creatures, *player_pos, offset, &mut draw_batch),
                 }
             }
-            
-            _ => {}
         }
     }
     
     draw_batch.submit(7000).expect("Batch error");
 }
 
-fn has_clear_path_for_dash(map: &Map, start: Point, end: Point) -> bool {
-    let mut current = start;
-    let dx = (end.x - start.x).abs();
-    let dy = (end.y - start.y).abs();
-    let sx = if start.x < end.x { 1 } else { -1 };
-    let sy = if start.y < end.y { 1 } else { -1 };
-    let mut err = dx - dy;
+// Dots on every tile in reach; blasts also preview their area around the cursor
+fn highlight_aimed(
+    ability: &Ability,
+    map: &Map,
+    creatures: &Creatures,
+    player_pos: Point,
+    cursor: Point,
+    offset: Point,
+    draw_batch: &mut DrawBatch
+) {
+    let cursor_valid = ability.valid_target(map, creatures, player_pos, cursor);
+    let is_blast = ability.is_blast();
+    let blast_area = ability.area(map, creatures, player_pos, cursor);
 
-    loop {
-        if current != start {
-            if current == end {
-                return true;
+    for y in (player_pos.y - ability.range)..=(player_pos.y + ability.range) {
+        for x in (player_pos.x - ability.range)..=(player_pos.x + ability.range) {
+            let target_pos = Point::new(x, y);
+            if !ability.valid_target(map, creatures, player_pos, target_pos) {
+                continue;
             }
-            
-            if !map.can_enter_tile(current) {
-                return false;
-            }
-        }
 
-        let e2 = 2 * err;
-        if e2 > -dy {
-            err -= dy;
-            current.x += sx;
-        }
-        if e2 < dx {
-            err += dx;
-            current.y += sy;
+            let (color, glyph) = if is_blast && blast_area.contains(&target_pos) {
+                if cursor_valid {
+                    (ColorPair::new(ORANGE, BLACK), to_cp437('*'))
+                } else {
+                    (ColorPair::new(RED, BLACK), to_cp437('x'))
+                }
+            } else {
+                (ColorPair::new(ability.color, BLACK), to_cp437('·'))
+            };
+            set_on_screen(target_pos, offset, color, glyph, draw_batch);
         }
+    }
 
-        if current == end {
-            return true;
-        }
+    if is_blast && cursor_valid {
+        draw_line_of_sight(player_pos, cursor, offset, ColorPair::new(GREEN, BLACK), draw_batch);
     }
 }
 
-fn has_line_of_sight(map: &Map, start: Point, end: Point) -> bool {
-    let mut current = start;
-    let dx = (end.x - start.x).abs();
-    let dy = (end.y - start.y).abs();
-    let sx = if start.x < end.x { 1 } else { -1 };
-    let sy = if start.y < end.y { 1 } else { -1 };
-    let mut err = dx - dy;
+// Rings round every enemy the bolt can start on, and the arcs it would take from the one under the cursor
+fn highlight_chain(
+    ability: &Ability,
+    map: &Map,
+    creatures: &Creatures,
+    player_pos: Point,
+    cursor: Point,
+    offset: Point,
+    draw_batch: &mut DrawBatch
+) {
+    creatures.enemies
+        .iter()
+        .filter(|pos| ability.valid_target(map, creatures, player_pos, **pos))
+        .for_each(|pos| set_on_screen(*pos, offset, ColorPair::new(ability.color, BLACK), to_cp437('o'), draw_batch));
 
-    loop {
-        if current != start {
-            if current == end {
-                return true;
-            }
-            
-            if !map.can_enter_tile(current) {
-                return false;
-            }
-        }
+    if ability.valid_target(map, creatures, player_pos, cursor) {
+        let hits = ability.area(map, creatures, player_pos, cursor);
+        draw_line_of_sight(player_pos, cursor, offset, ColorPair::new(GREEN, BLACK), draw_batch);
+        hits.windows(2).for_each(|arc| {
+            draw_line_of_sight(arc[0], arc[1], offset, ColorPair::new(ability.color, BLACK), draw_batch);
+        });
+        hits.iter().for_each(|pos| set_on_screen(*pos, offset, ColorPair::new(ORANGE, BLACK), to_cp437('*'), draw_batch));
+    }
+}
 
-        let e2 = 2 * err;
-        if e2 > -dy {
-            err -= dy;
-            current.x += sx;
-        }
-        if e2 < dx {
-            err += dx;
-            current.y += sy;
-        }
+// Self-cast spells show what they'll touch straight away: the nova's area, the healed caster or every possible blink landing
+fn highlight_caster(
+    ability: &Ability,
+    map: &Map,
+    creatures: &Creatures,
+    player_pos: Point,
+    offset: Point,
+    draw_batch: &mut DrawBatch
+) {
+    let color = ColorPair::new(ability.color, BLACK);
+    if ability.effect == AbilityEffect::Blink {
+        ability.blink_destinations(map, creatures, player_pos)
+            .iter()
+            .for_each(|pos| set_on_screen(*pos, offset, color, to_cp437('?'), draw_batch));
+        return;
+    }
 
-        if current == end {
-            return true;
-        }
+    let area = ability.area(map, creatures, player_pos, player_pos);
+    if area.len() == 1 {
+        set_on_screen(player_pos, offset, color, to_cp437('+'), draw_batch);
+    } else {
+        area.iter()
+            .filter(|pos| **pos != player_pos && map.can_enter_tile(**pos))
+            .for_each(|pos| set_on_screen(*pos, offset, color, to_cp437('*'), draw_batch));
     }
 }
 
-fn draw_line_of_sight(start: Point, end: Point, camera_offset: Point, draw_batch: &mut DrawBatch) {
+fn set_on_screen(pos: Point, camera_offset: Point, color: ColorPair, glyph: FontCharType, draw_batch: &mut DrawBatch) {
+    let screen_pos = pos - camera_offset;
+    if screen_pos.x >= 0 && screen_pos.x < DISPLAY_WIDTH && 
+       screen_pos.y >= 0 && screen_pos.y < DISPLAY_HEIGHT {
+        draw_batch.set(screen_pos, color, glyph);
+    }
+}
+
+fn draw_line_of_sight(start: Point, end: Point, camera_offset: Point, color: ColorPair, draw_batch: &mut DrawBatch) {
     let mut current = start;
     let dx = (end.x - start.x).abs();
     let dy = (end.y - start.y).abs();
@@ -187,15 +156,7 @@ fn draw_line_of_sight(start: Point, end: Point, camera_offset: Point, draw_batch
 
     loop {
         if current != start && current != end {
-            let screen_pos = current - camera_offset;
-            if screen_pos.x >= 0 && screen_pos.x < DISPLAY_WIDTH && 
-               screen_pos.y >= 0 && screen_pos.y < DISPLAY_HEIGHT {
-                draw_batch.set(
-                    screen_pos,
-                    ColorPair::new(GREEN, BLACK),
-                    to_cp437('.')
-                );
-            }
+            set_on_screen(current, camera_offset, color, to_cp437('.'), draw_batch);
         }
 
         if current == end {
@@ -0,0 +1,121 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Abilities)]
+#[read_component(Mana)]
+#[read_component(Player)]
+#[read_component(Enemy)]
+#[read_component(Ally)]
+#[read_component(FieldOfView)]
+pub fn targeting_input(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] key: &Option<VirtualKeyCode>,
+    #[resource] bindings: &Bindings,
+    #[resource] held: &HeldKeys,
+    #[resource] mouse_buttons: &Option<(i32, i32, bool, bool, bool)>,
+    #[resource] camera: &Camera,
+    #[resource] turn_state: &mut TurnState,
+    #[resource] targeting_state: &mut TargetingState,
+    #[resource] map: &Map
+) {
+    let (index, cursor) = match (targeting_state.ability(), targeting_state.cursor()) {
+        (Some(index), Some(cursor)) => (index, cursor),
+        _ => return,
+    };
+    let mut players = <(Entity, &Point, &Abilities, &Mana)>::query()
+        .filter(component::<Player>());
+    let (player_entity, player_point, ability, mana) = match players.iter(ecs).nth(0) {
+        Some((entity, pos, abilities, mana)) => match abilities.0.get(index) {
+            Some(ability) => (*entity, *pos, ability, mana),
+            None => return,
+        },
+        None => return,
+    };
+
+    // Right-click cancels targeting
+    if let Some((_, _, _, true, _)) = *mouse_buttons {
+        *targeting_state = TargetingState::None;
+        return;
+    }
+
+    // A click fires at the clicked tile and Confirm at the cursor; self-cast abilities also go off
+    // on a second press of their hotkey
+    let clicked = match *mouse_buttons {
+        Some((x, y, true, _, _)) => Some(Point::new(x, y) + Point::new(camera.left_x, camera.top_y)),
+        _ => None,
+    };
+    let action = key.and_then(|key| bindings.action_with_held(key, held));
+    let confirmed = action == Some(Action::Confirm);
+    let recast = ability.is_self_cast() && action == Some(Action::CastAbility(index));
+    if clicked.is_some() || confirmed || recast {
+        let target = if ability.is_self_cast() {
+            player_point
+        } else {
+            clicked.unwrap_or(cursor)
+        };
+        let creatures = Creatures::gather(ecs);
+        if mana.current >= ability.cost && ability.is_ready() && ability.valid_target(map, &creatures, player_point, target) {
+            commands.push(((), WantsToCast {
+                caster: player_entity,
+                ability: index,
+                target,
+                from_scroll: false,
+            }));
+
+            *turn_state = TurnState::PlayerTurn;
+            *targeting_state = TargetingState::None;
+        }
+        return;
+    }
+
+    match action {
+        Some(Action::Cancel) => {
+            *targeting_state = TargetingState::None;
+        }
+        Some(action) if action.direction().is_some() => {
+            let delta = action.direction().unwrap_or_else(Point::zero);
+            // The cursor stays on screen so it can always be seen
+            let moved = cursor + delta;
+            if moved.x >= camera.left_x && moved.x < camera.left_x + DISPLAY_WIDTH &&
+               moved.y >= camera.top_y && moved.y < camera.top_y + DISPLAY_HEIGHT {
+                targeting_state.move_cursor_to(moved);
+            }
+        }
+        Some(Action::NextTarget) => {
+            let targets = targets_in_range(ecs, player_entity, player_point, ability);
+            if !targets.is_empty() {
+                let next = match targets.iter().position(|pos| *pos == cursor) {
+                    Some(current) if held.shift() => (current + targets.len() - 1) % targets.len(),
+                    Some(current) => (current + 1) % targets.len(),
+                    None if held.shift() => targets.len() - 1,
+                    None => 0,
+                };
+                targeting_state.move_cursor_to(targets[next]);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Visible enemies within the ability's range, nearest first; Tab cycles through these
+pub(super) fn targets_in_range(ecs: &SubWorld, player: Entity, player_pos: Point, ability: &Ability) -> Vec<Point> {
+    let visible = match ecs.entry_ref(player) {
+        Ok(entry) => match entry.get_component::<FieldOfView>() {
+            Ok(fov) => fov.visible_tiles.clone(),
+            Err(_) => return Vec::new(),
+        },
+        Err(_) => return Vec::new(),
+    };
+    let mut targets: Vec<Point> = <&Point>::query()
+        .filter(component::<Enemy>())
+        .iter(ecs)
+        .filter(|pos| visible.contains(pos))
+        .filter(|pos| DistanceAlg::Pythagoras.distance2d(player_pos, **pos) <= ability.range as f32)
+        .copied()
+        .collect();
+    // Ties are broken by position so the order doesn't shuffle between presses
+    targets.sort_by_key(|pos| (DistanceAlg::PythagorasSquared.distance2d(player_pos, *pos) as i32, pos.y, pos.x));
+    targets
+}
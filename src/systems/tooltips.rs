@@ -4,30 +4,58 @@ use crate::prelude::*;
 #[read_component(Point)]
 #[read_component(Name)]
 #[read_component(Health)]
+#[read_component(FieldOfView)]
+#[read_component(Player)]
+#[read_component(Burning)]
+#[read_component(Poisoned)]
+#[read_component(Stunned)]
+#[read_component(Slowed)]
+#[read_component(Hasted)]
+#[read_component(Regenerating)]
+#[read_component(Awareness)]
 pub fn tooltips(
     ecs: &SubWorld,
     #[resource] mouse_pos: &Point,
     #[resource] camera: &Camera
 ) {
     let mut positions = <(Entity, &Point, &Name)>::query();
+    let mut fov = <&FieldOfView>::query().filter(component::<Player>());
+    let player_fov = match fov.iter(ecs).nth(0) {
+        Some(player_fov) => player_fov,
+        None => return,
+    };
     let offset = Point::new(camera.left_x, camera.top_y);
     let map_pos = *mouse_pos + offset;
     let mut draw_batch = DrawBatch::new();
     draw_batch.target(2);
     positions
         .iter(ecs)
-        .filter(|(_, pos, _)| **pos == map_pos )
+        .filter(|(_, pos, _)| **pos == map_pos && player_fov.visible_tiles.contains(&map_pos))
         .for_each(|(entity, _, name) | {
             let screen_pos = *mouse_pos * 4;
-            let display = if let Ok(health) = ecs.entry_ref(*entity)
-                .unwrap()
-                .get_component::<Health>() 
-            {
+            let entry = ecs.entry_ref(*entity).unwrap();
+            let mut display = if let Ok(health) = entry.get_component::<Health>() {
                 format!("{} : {} hp", &name.0, health.current)
             } else {
                 name.0.clone()
             };
+            // Worth knowing before trying to sneak past
+            if let Some(label) = entry.get_component::<Awareness>().ok().and_then(|awareness| awareness.state.label()) {
+                display = format!("{} ({})", display, label);
+            }
             draw_batch.print(screen_pos, &display);
+
+            // Active effects are listed underneath, one per line
+            StatusEffect::active_on(&entry)
+                .iter()
+                .enumerate()
+                .for_each(|(i, (effect, turns))| {
+                    draw_batch.print_color(
+                        screen_pos + Point::new(0, i as i32 + 1),
+                        format!("{} ({})", effect.name(), turns),
+                        ColorPair::new(effect.color(), BLACK)
+                    );
+                });
         });
     draw_batch.submit(10100).expect("Batch error");
 }
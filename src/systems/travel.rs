@@ -0,0 +1,131 @@
+use crate::prelude::*;
+
+// Takes one step of a walk started by travel_input each time the player could act
+#[system]
+#[read_component(Point)]
+#[read_component(Player)]
+#[read_component(Enemy)]
+#[read_component(Ally)]
+#[read_component(Health)]
+#[read_component(FieldOfView)]
+#[read_component(Travelling)]
+#[read_component(Stunned)]
+pub fn travel(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] turn_state: &mut TurnState,
+    #[resource] map: &Map,
+    #[resource] distance_maps: &mut DistanceMaps,
+    #[resource] game_log: &mut GameLog
+) {
+    if *turn_state != TurnState::AwaitingInput {
+        return;
+    }
+    let mut players = <(Entity, &Point, &Health, &FieldOfView, &Travelling)>::query()
+        .filter(component::<Player>());
+    let (player, player_pos, health, fov, travelling) = match players.iter(ecs).nth(0) {
+        Some(player) => player,
+        None => return,
+    };
+    let enemy_in_view = <&Point>::query()
+        .filter(component::<Enemy>())
+        .iter(ecs)
+        .any(|pos| fov.visible_tiles.contains(pos));
+
+    let stunned = ecs.entry_ref(*player)
+        .map_or(false, |entry| entry.get_component::<Stunned>().is_ok());
+
+    // Stunned, the player has to let turns pass by hand as they would without travelling
+    let stop_reason = if stunned {
+        Some("You are stunned and stop")
+    } else if enemy_in_view {
+        Some("You spot an enemy and stop")
+    } else if health.current < travelling.health {
+        Some("You are hurt and stop")
+    } else {
+        None
+    };
+    if let Some(reason) = stop_reason {
+        commands.remove_component::<Travelling>(*player);
+        game_log.add(LogCategory::Travel, reason);
+        return;
+    }
+
+    let next_step = match travelling.goal {
+        TravelGoal::Tile(tile) => step_towards(map, *player_pos, tile),
+        TravelGoal::Explore => {
+            // Whatever is left may be walled off from here
+            let step = distance_maps.explore(map).downhill(map, *player_pos, |_| false);
+            if step.is_none() {
+                game_log.add(LogCategory::Travel, "There is nothing left to explore");
+            }
+            step
+        }
+    };
+
+    match next_step {
+        Some(destination) => {
+            commands.push(((), WantsToMove {
+                entity: *player,
+                destination,
+            }));
+            // Summoned allies step aside, just as when walking into them by hand
+            let mut allies = <(Entity, &Point)>::query().filter(component::<Ally>());
+            if let Some((ally, _)) = allies.iter(ecs).find(|(_, ally_pos)| **ally_pos == destination) {
+                commands.push(((), WantsToMove {
+                    entity: *ally,
+                    destination: *player_pos,
+                }));
+            }
+            commands.add_component(*player, Travelling { health: health.current, ..*travelling });
+            *turn_state = TurnState::PlayerTurn;
+        }
+        // Arrived, or there's no way there
+        None => commands.remove_component::<Travelling>(*player),
+    }
+}
+
+fn step_towards(map: &Map, start: Point, goal: Point) -> Option<Point> {
+    if start == goal {
+        return None;
+    }
+    let path = a_star_search(map.point2d_to_index(start), map.point2d_to_index(goal), map);
+    if path.success && path.steps.len() > 1 {
+        Some(map.index_to_point2d(path.steps[1]))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_stunned_traveller_stays_put() {
+        let mut ecs = World::default();
+        let mut resources = Resources::default();
+        resources.insert(TurnState::AwaitingInput);
+        // Pathfinding never steps onto the edge, so the walk runs along the middle row
+        resources.insert(Map::new(10, 3));
+        resources.insert(DistanceMaps::default());
+        resources.insert(GameLog::new());
+        let player = ecs.push((
+            Player,
+            Point::new(1, 1),
+            Health { current: 10, max: 10 },
+            FieldOfView::new(8),
+            Travelling { goal: TravelGoal::Tile(Point::new(8, 1)), health: 10 },
+            Stunned { turns: 2 },
+        ));
+
+        let mut schedule = Schedule::builder().add_system(travel_system()).build();
+        schedule.execute(&mut ecs, &mut resources);
+
+        assert_eq!(<&WantsToMove>::query().iter(&ecs).count(), 0);
+        let entry = ecs.entry(player).unwrap();
+        assert_eq!(*entry.get_component::<Point>().unwrap(), Point::new(1, 1));
+        assert!(entry.get_component::<Travelling>().is_err());
+        assert_eq!(*resources.get::<TurnState>().unwrap(), TurnState::AwaitingInput);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Player)]
+#[read_component(Enemy)]
+#[read_component(Health)]
+#[read_component(FieldOfView)]
+#[read_component(Travelling)]
+pub fn travel_input(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] key: &Option<VirtualKeyCode>,
+    #[resource] bindings: &Bindings,
+    #[resource] mouse_buttons: &Option<(i32, i32, bool, bool, bool)>,
+    #[resource] camera: &Camera,
+    #[resource] turn_state: &TurnState,
+    #[resource] targeting_state: &TargetingState,
+    #[resource] map: &Map,
+    #[resource] game_log: &mut GameLog,
+    #[state] button_was_down: &mut bool
+) {
+    let button_down = matches!(*mouse_buttons, Some((_, _, true, _, _)));
+    let pressed = button_down && !*button_was_down;
+    *button_was_down = button_down;
+    if targeting_state.is_targeting() || *turn_state != TurnState::AwaitingInput {
+        return;
+    }
+    let mut players = <(Entity, &Point, &Health, &FieldOfView, Option<&Travelling>)>::query()
+        .filter(component::<Player>());
+    let (player, player_pos, health, fov, travelling) = match players.iter(ecs).nth(0) {
+        Some(player) => player,
+        None => return,
+    };
+    // The button reads as held for a few frames, so a click is only ever a destination
+    let clicked = match *mouse_buttons {
+        Some((x, y, true, _, _)) => Some(Point::new(x, y) + Point::new(camera.left_x, camera.top_y))
+            .filter(|tile| *tile != *player_pos && map.can_enter_tile(*tile))
+            .filter(|tile| map.revealed_tiles[map.point2d_to_index(*tile)]),
+        _ => None,
+    };
+
+    match travelling {
+        // Clicking somewhere else heads there instead; any key takes back control
+        Some(travelling) => {
+            if key.is_some() {
+                commands.remove_component::<Travelling>(*player);
+                game_log.add(LogCategory::Travel, "You stop");
+            } else if let Some(tile) = clicked {
+                commands.add_component(*player, Travelling { goal: TravelGoal::Tile(tile), ..*travelling });
+            }
+        }
+        None => {
+            // Only a fresh press, so holding the button down doesn't repeat the refusal every frame
+            let goal = match (clicked.filter(|_| pressed), key.and_then(|key| bindings.action(key))) {
+                (Some(tile), _) => TravelGoal::Tile(tile),
+                (_, Some(Action::Explore)) => TravelGoal::Explore,
+                _ => return,
+            };
+            let enemy_in_view = <&Point>::query()
+                .filter(component::<Enemy>())
+                .iter(ecs)
+                .any(|pos| fov.visible_tiles.contains(pos));
+            if enemy_in_view {
+                game_log.add(LogCategory::Travel, "Not with enemies in sight");
+            } else {
+                commands.add_component(*player, Travelling { goal, health: health.current });
+            }
+        }
+    }
+}
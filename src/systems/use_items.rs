@@ -0,0 +1,126 @@
+use crate::prelude::*;
+use legion::world::EntryRef;
+
+#[system]
+#[read_component(ActivateItem)]
+#[read_component(ProvidesHealing)]
+#[read_component(ProvidesMana)]
+#[read_component(ProvidesSpell)]
+#[read_component(Health)]
+#[read_component(Mana)]
+#[read_component(Name)]
+#[read_component(FieldOfView)]
+#[read_component(Point)]
+#[read_component(Enemy)]
+#[read_component(Equippable)]
+#[read_component(Equipped)]
+#[read_component(EquipmentBonus)]
+#[read_component(Abilities)]
+pub fn use_items(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] game_log: &mut GameLog
+) {
+    <(Entity, &ActivateItem)>::query().iter(ecs).for_each(|(entity, activate)| {
+        let (item, user) = match (ecs.entry_ref(activate.item), ecs.entry_ref(activate.used_by)) {
+            (Ok(item), Ok(user)) => (item, user),
+            _ => {
+                commands.remove(*entity);
+                return;
+            }
+        };
+        let item_name = item.get_component::<Name>().map_or_else(|_| "item".to_string(), |name| name.0.clone());
+        let mut consumed = true;
+
+        if let (Ok(healing), Ok(health)) = (item.get_component::<ProvidesHealing>(), user.get_component::<Health>()) {
+            commands.add_component(activate.used_by, Health {
+                current: (health.current + healing.amount).min(health.max),
+                max: health.max,
+            });
+            game_log.add(LogCategory::Item, format!("Player drinks a {} and recovers {} health", item_name, healing.amount));
+        }
+
+        if let (Ok(restore), Ok(mana)) = (item.get_component::<ProvidesMana>(), user.get_component::<Mana>()) {
+            commands.add_component(activate.used_by, Mana {
+                current: (mana.current + restore.amount).min(mana.max),
+                max: mana.max,
+            });
+            game_log.add(LogCategory::Item, format!("Player drinks a {} and recovers {} mana", item_name, restore.amount));
+        }
+
+        if let Ok(spell) = item.get_component::<ProvidesSpell>() {
+            // The scroll seeks out the closest enemy the reader can see
+            let target = match (user.get_component::<Point>(), user.get_component::<FieldOfView>()) {
+                (Ok(user_pos), Ok(fov)) => <&Point>::query()
+                    .filter(component::<Enemy>())
+                    .iter(ecs)
+                    .filter(|pos| fov.visible_tiles.contains(pos))
+                    .min_by_key(|pos| DistanceAlg::PythagorasSquared.distance2d(*user_pos, **pos) as i32)
+                    .copied(),
+                _ => None,
+            };
+
+            // The scroll powers the reader's own version of the spell
+            let ability = user.get_component::<Abilities>().ok().and_then(|abilities| abilities.find(&spell.0));
+
+            if let (Some(target), Some(ability)) = (target, ability) {
+                commands.push(((), WantsToCast {
+                    caster: activate.used_by,
+                    ability,
+                    target,
+                    from_scroll: true,
+                }));
+                game_log.add(LogCategory::Item, format!("Player reads a {}", item_name));
+            } else if ability.is_none() {
+                consumed = false;
+                game_log.add(LogCategory::Item, format!("The {} makes no sense to you", item_name));
+            } else {
+                consumed = false;
+                game_log.add(LogCategory::Item, format!("No enemy in sight to aim the {} at", item_name));
+            }
+        }
+
+        if let Ok(equippable) = item.get_component::<Equippable>() {
+            consumed = false;
+            let mut change = item.get_component::<EquipmentBonus>().copied().unwrap_or_default();
+            // Whatever already fills the slot goes back into the pack
+            if let Some((worn, worn_bonus)) = EquipmentBonus::in_slot(ecs, activate.used_by, equippable.slot) {
+                commands.remove_component::<Equipped>(worn);
+                commands.add_component(worn, Carried(activate.used_by));
+                change = change.minus(worn_bonus);
+                if let Some(worn_name) = ecs.entry_ref(worn).ok().and_then(|entry| entry.get_component::<Name>().ok().cloned()) {
+                    game_log.add(LogCategory::Item, format!("Player takes off the {}", worn_name.0));
+                }
+            }
+            commands.remove_component::<Carried>(activate.item);
+            commands.add_component(activate.item, Equipped {
+                owner: activate.used_by,
+                slot: equippable.slot,
+            });
+            apply_equipment_change(commands, &user, activate.used_by, change);
+            game_log.add(LogCategory::Item, format!("Player equips the {} ({})", item_name, change.describe()));
+        }
+
+        if consumed {
+            commands.remove(activate.item);
+        }
+        commands.remove(*entity);
+    });
+}
+
+// Melee stats are summed in combat, but mana and spell stats live on the wearer and are adjusted here
+fn apply_equipment_change(commands: &mut CommandBuffer, user: &EntryRef, entity: Entity, change: EquipmentBonus) {
+    if let Ok(mana) = user.get_component::<Mana>() {
+        let max = mana.max + change.max_mana;
+        commands.add_component(entity, Mana {
+            current: mana.current.min(max),
+            max,
+        });
+    }
+    if let Ok(abilities) = user.get_component::<Abilities>() {
+        let abilities = abilities
+            .with(Ability::is_movement, |ability| ability.range += change.dash_range)
+            .with(Ability::deals_damage, |ability| ability.add_damage(change.spell_damage));
+        commands.add_component(entity, abilities);
+    }
+}
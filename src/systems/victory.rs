@@ -0,0 +1,19 @@
+use crate::prelude::*;
+
+#[system]
+pub fn victory(#[resource] seed: &Seed) {
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.target(2);
+
+    draw_batch.print_color_centered(30, "You have won!", ColorPair::new(GREEN, BLACK));
+    draw_batch.print_color_centered(
+        33,
+        format!("You fought your way through all {} floors of the dungeon.", FINAL_LEVEL),
+        ColorPair::new(WHITE, BLACK)
+    );
+    draw_batch.print_color_centered(34, format!("Seed: {}", seed.0), ColorPair::new(GRAY, BLACK));
+    draw_batch.print_color_centered(38, "Press 1 to play again with a new seed.", ColorPair::new(GREEN, BLACK));
+    draw_batch.print_color_centered(39, "Press 2 to replay the same seed.", ColorPair::new(GREEN, BLACK));
+
+    draw_batch.submit(12000).expect("Batch error");
+}
@@ -2,15 +2,31 @@ use crate::prelude::*;
 
 #[system]
 #[read_component(Enemy)]
+#[read_component(Player)]
+#[read_component(Point)]
 pub fn wave_management(
     ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] rng: &mut RandomNumberGenerator,
     #[resource] wave_manager: &mut WaveManager,
+    #[resource] game_log: &mut GameLog,
+    #[resource] level: &DungeonLevel,
 ) {
     if wave_manager.wave_active {
         let enemy_count = <&Enemy>::query().iter(ecs).count();
         wave_manager.enemies_remaining = enemy_count as i32;
         
         if enemy_count == 0 {
+            game_log.add(LogCategory::Wave, format!("Wave {} cleared!", wave_manager.current_wave));
+            
+            // Each cleared wave leaves a reward at the player's feet
+            if let Some(player_pos) = <&Point>::query().filter(component::<Player>()).iter(ecs).nth(0) {
+                spawn_item(commands, ItemType::random(rng), *player_pos);
+                game_log.add(LogCategory::Item, "Something drops at your feet (G to pick up)");
+            }
+            if wave_manager.current_wave == WAVES_PER_LEVEL && level.0 < FINAL_LEVEL {
+                game_log.add(LogCategory::Level, "The way down is open. Find the stairs!");
+            }
             wave_manager.wave_active = false;
             wave_manager.spawn_timer = 2;
             wave_manager.current_wave += 1;
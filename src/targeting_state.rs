@@ -1,12 +1,34 @@
+use crate::prelude::*;
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum TargetingState {
     None,
-    SelectingDashTarget,
-    SelectingFireballTarget,
+    // `ability` indexes the player's Abilities; `cursor` is the map tile being aimed at
+    Selecting { ability: usize, cursor: Point },
 }
 
 impl TargetingState {
     pub fn is_targeting(&self) -> bool {
         *self != TargetingState::None
     }
-}
\ No newline at end of file
+
+    pub fn ability(&self) -> Option<usize> {
+        match self {
+            TargetingState::Selecting { ability, .. } => Some(*ability),
+            TargetingState::None => None,
+        }
+    }
+
+    pub fn cursor(&self) -> Option<Point> {
+        match self {
+            TargetingState::Selecting { cursor, .. } => Some(*cursor),
+            TargetingState::None => None,
+        }
+    }
+
+    pub fn move_cursor_to(&mut self, target: Point) {
+        if let TargetingState::Selecting { cursor, .. } = self {
+            *cursor = target;
+        }
+    }
+}
@@ -2,5 +2,11 @@
 pub enum TurnState {
     AwaitingInput,
     PlayerTurn,
-    MonsterTurn
-}
\ No newline at end of file
+    MonsterTurn,
+    NextLevel,
+    GameOver,
+    Victory,
+    ViewingLog,
+    ShowingInventory,
+    ChoosingUpgrade
+}
@@ -0,0 +1,69 @@
+use crate::prelude::*;
+
+pub const UPGRADE_CHOICES: usize = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Upgrade {
+    MaxHealth,
+    MaxMana,
+    Power,
+    Defense,
+    BlastRadius,
+    SpellDamage,
+    CheaperDash,
+    DashRange,
+}
+
+impl Upgrade {
+    const ALL: [Upgrade; 8] = [
+        Upgrade::MaxHealth,
+        Upgrade::MaxMana,
+        Upgrade::Power,
+        Upgrade::Defense,
+        Upgrade::BlastRadius,
+        Upgrade::SpellDamage,
+        Upgrade::CheaperDash,
+        Upgrade::DashRange,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Upgrade::MaxHealth => "Toughness: +5 max health",
+            Upgrade::MaxMana => "Focus: +3 max mana",
+            Upgrade::Power => "Strength: +1 power",
+            Upgrade::Defense => "Guard: +1 defense",
+            Upgrade::BlastRadius => "Inferno: +1 blast radius",
+            Upgrade::SpellDamage => "Pyromancy: +2 spell damage",
+            Upgrade::CheaperDash => "Nimble: movement spells cost 1 less mana",
+            Upgrade::DashRange => "Stride: +1 movement spell range",
+        }
+    }
+
+    // A handful of distinct upgrades, skipping any that would do nothing for these abilities
+    pub fn roll(rng: &mut RandomNumberGenerator, abilities: &Abilities) -> Vec<Upgrade> {
+        let mut pool: Vec<Upgrade> = Upgrade::ALL
+            .iter()
+            .filter(|upgrade| upgrade.applies_to(abilities))
+            .copied()
+            .collect();
+        let mut choices = Vec::new();
+        while choices.len() < UPGRADE_CHOICES && !pool.is_empty() {
+            let idx = rng.range(0, pool.len());
+            choices.push(pool.remove(idx));
+        }
+        choices
+    }
+
+    fn applies_to(self, abilities: &Abilities) -> bool {
+        match self {
+            Upgrade::BlastRadius => abilities.0.iter().any(Ability::is_blast),
+            Upgrade::SpellDamage => abilities.0.iter().any(Ability::deals_damage),
+            Upgrade::CheaperDash => abilities.0.iter().any(|ability| ability.is_movement() && ability.cost > 1),
+            Upgrade::DashRange => abilities.0.iter().any(Ability::is_movement),
+            _ => true,
+        }
+    }
+}
+
+// The upgrades on offer, rolled once when the screen opens so they stay put while it's shown
+pub struct UpgradeChoices(pub Vec<Upgrade>);